@@ -0,0 +1,74 @@
+//Acts as one register for trying out syncing with two local databases, see the readme
+//  SMALANDS_SERVER_KEY=<key> sync <database> <server> [item price]
+//Sells one of each available item, or adds or changes the item first when given, then syncs once
+//and prints the menu as it is after the sync
+use {
+    chrono::Local,
+    register_rs_backend::{
+        items::Item,
+        receipts::{Payment, Receipt},
+        sync,
+    },
+    std::process::ExitCode,
+};
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let [database, server, rest @ ..] = args.as_slice() else {
+        eprintln!("sync <database> <server> [item price]");
+        return ExitCode::FAILURE;
+    };
+
+    match run(database, server, rest).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{e:?}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run(database: &str, server: &str, rest: &[String]) -> register_rs_backend::Result<()> {
+    register_rs_backend::connect(database)?;
+    register_rs_backend::set_register(database.to_string())?;
+    let key = std::env::var("SMALANDS_SERVER_KEY").unwrap_or_default();
+    sync::connect(server, &key)?;
+
+    let menu = Item::get_all().await?;
+    if let [name, price] = rest {
+        let price = price.parse().unwrap_or_default();
+        match menu.iter().find(|item| item.name() == name) {
+            Some(item) => item.clone().update(item.clone().with_price(price)).await?,
+            None => {
+                Item::new()
+                    .with_name(name.clone())
+                    .with_price(price)
+                    .insert_new()
+                    .await?
+            }
+        }
+    }
+
+    let mut receipt = Receipt::default()
+        .with_time(Local::now())
+        .with_payment(Payment::Cash);
+    for item in Item::get_all_available().await? {
+        if !item.is_special() {
+            receipt.insert(item, 1);
+        }
+    }
+    if !receipt.is_empty() {
+        receipt.insert_sale().await?;
+    }
+    println!("Queued: {}", sync::pending().await?);
+
+    if let Err(e) = sync::sync().await {
+        println!("Not synced: {e:?}");
+    }
+    println!("Queued after sync: {}", sync::pending().await?);
+    for item in Item::get_all().await? {
+        println!("{} {} kr", item.name(), item.price());
+    }
+    Ok(())
+}
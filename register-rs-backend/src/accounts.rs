@@ -0,0 +1,98 @@
+use crate::Result;
+use chrono::{DateTime, Local};
+use getset::{Getters, WithSetters};
+use rusqlite::{Connection, params};
+use serde::{Deserialize, Serialize};
+
+//Special item sold when topping up, one per kr
+pub const TOP_UP: &str = "Saldo";
+
+//Prepaid card, the balance is the sum of the ledger
+#[derive(Debug, Default, Clone, PartialEq, Eq, Getters, WithSetters, Serialize, Deserialize)]
+#[getset(get = "pub", set_with = "pub")]
+pub struct Account {
+    card: String,
+    holder: String,
+    balance: i32,
+}
+
+impl Account {
+    pub fn new(card: String, holder: String) -> Self {
+        Self {
+            card,
+            holder,
+            balance: 0,
+        }
+    }
+
+    fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            card: row.get("card")?,
+            holder: row.get("holder")?,
+            balance: row.get("balance")?,
+        })
+    }
+
+    pub async fn get_all() -> Result<Vec<Self>> {
+        remote!("/accounts");
+        select!(
+            "SELECT accounts.card, accounts.holder, COALESCE(SUM(account_ledger.amount), 0) AS balance
+                FROM accounts
+                    LEFT JOIN account_ledger ON account_ledger.card = accounts.card
+                GROUP BY accounts.card
+                ORDER BY accounts.holder",
+            Self::from_row
+        )
+    }
+
+    pub async fn insert_new(self) -> Result<()> {
+        remote!("/accounts/new", self);
+        insert!(
+            "INSERT INTO accounts (card, holder) VALUES (?1, ?2)",
+            params![self.card, self.holder]
+        )?;
+        Ok(())
+    }
+
+    //Part of the sale, see Receipt::insert_sale
+    pub(crate) fn credit(
+        conn: &Connection,
+        card: &str,
+        time: DateTime<Local>,
+        amount: i32,
+    ) -> Result<()> {
+        if amount <= 0 {
+            return Err(crate::Error::InvalidAmount);
+        }
+        conn.prepare_cached("INSERT INTO account_ledger (card, time, amount) VALUES (?1, ?2, ?3)")?
+            .execute(params![card, time, amount])?;
+        Ok(())
+    }
+
+    //Checked and written in one statement so two registers can not overdraw a card together, a
+    //negative amount would credit the card instead
+    pub(crate) fn debit(
+        conn: &Connection,
+        card: &str,
+        time: DateTime<Local>,
+        amount: i32,
+    ) -> Result<()> {
+        if amount <= 0 {
+            return Err(crate::Error::InvalidAmount);
+        }
+        let debited = conn
+            .prepare_cached(
+                "INSERT INTO account_ledger (card, time, amount)
+                    SELECT ?1, ?2, -?3
+                    WHERE EXISTS (SELECT 1 FROM accounts WHERE card=?1)
+                        AND (SELECT COALESCE(SUM(amount), 0) FROM account_ledger
+                            WHERE card=?1) >= ?3",
+            )?
+            .execute(params![card, time, amount])?;
+        if debited == 0 {
+            Err(crate::Error::InsufficientFunds)
+        } else {
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,55 @@
+//Shares one database between several registers on the same network
+//  SMALANDS_SERVER_KEY=<key> server [address] [database]
+//Listens on 0.0.0.0:7878 and uses the same database as the register app on this computer by
+//default, so the register next to it keeps working locally while the others connect to it.
+//Registers have to send the key set in the environment, the server does not start without one
+use std::{path::PathBuf, process::ExitCode};
+
+const ADDRESS: &str = "0.0.0.0:7878";
+const KEY: &str = "SMALANDS_SERVER_KEY";
+
+fn config_path() -> Option<PathBuf> {
+    let mut path = dirs::config_dir()?;
+    path.push("smaland_register");
+    Some(path)
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let key = match std::env::var(KEY) {
+        Ok(key) if !key.trim().is_empty() => key.trim().to_string(),
+        _ => {
+            eprintln!("{KEY} has to be set to the key the registers use");
+            return ExitCode::FAILURE;
+        }
+    };
+    let mut args = std::env::args().skip(1);
+    let address = args.next().unwrap_or_else(|| ADDRESS.to_string());
+    let Some(dir) = config_path() else {
+        eprintln!("No config dir");
+        return ExitCode::FAILURE;
+    };
+    let database = args.next().map(PathBuf::from).unwrap_or(dir.join("db.db"));
+
+    //Kitchen tickets are printed by the server since the sale is stored here
+    let receipts = dir.join("receipts");
+    if let Err(e) = std::fs::create_dir_all(&receipts) {
+        eprintln!("Could not create {}: {e}", receipts.display());
+        return ExitCode::FAILURE;
+    }
+    if let Err(e) = register_rs_backend::connect(&database)
+        .and_then(|_| register_rs_backend::set_receipt_path(receipts))
+    {
+        eprintln!("Could not open {}: {e:?}", database.display());
+        return ExitCode::FAILURE;
+    }
+
+    println!("Serving {} on {address}", database.display());
+    match register_rs_backend::remote::serve(&address, &key).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Could not listen on {address}: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
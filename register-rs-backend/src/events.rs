@@ -0,0 +1,76 @@
+use crate::Result;
+use chrono::{DateTime, Local};
+use getset::{Getters, WithSetters};
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+
+//Pub nights, gasques etc. that are accounted for separately
+#[derive(Debug, Default, Clone, PartialEq, Eq, Getters, WithSetters, Serialize, Deserialize)]
+#[getset(get = "pub", set_with = "pub")]
+pub struct Event {
+    id: i64,
+    name: String,
+    start: DateTime<Local>,
+    end: DateTime<Local>,
+    #[getset(skip)]
+    active: bool,
+}
+
+impl Event {
+    pub fn new(name: String, start: DateTime<Local>, end: DateTime<Local>) -> Self {
+        Self {
+            name,
+            start,
+            end,
+            ..Default::default()
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            id: row.get("id")?,
+            name: row.get("name")?,
+            start: row.get("start_time")?,
+            end: row.get("end_time")?,
+            active: row.get("active")?,
+        })
+    }
+
+    pub async fn get_all() -> Result<Vec<Self>> {
+        remote!("/events");
+        select!(
+            "SELECT id, name, start_time, end_time, active FROM events
+                ORDER BY start_time DESC",
+            Self::from_row
+        )
+    }
+
+    pub async fn insert_new(self) -> Result<()> {
+        remote!("/events/new", self);
+        insert!(
+            "INSERT INTO events (name, start_time, end_time) VALUES (?1, ?2, ?3)",
+            params![self.name, self.start, self.end]
+        )?;
+        Ok(())
+    }
+
+    //Only one event can be active, new receipts are tagged with it
+    pub async fn change_active(self, active: bool) -> Result<()> {
+        remote!("/events/active", (self, active));
+        insert!(
+            "UPDATE events SET active = (id=?1 AND ?2) WHERE active OR id=?1",
+            params![self.id, active]
+        )?;
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for Event {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
@@ -0,0 +1,218 @@
+use {
+    serde::de::DeserializeOwned,
+    std::{sync::Arc, time::Duration},
+    tokio::{
+        io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+        net::TcpStream,
+    },
+};
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+//Largest request the server reads, item changes carrying a picture are the biggest
+const MAX_BODY: usize = 16 * 1024 * 1024;
+//The request line and every header, and how many headers are read, before the key is known
+const MAX_LINE: u64 = 8 * 1024;
+const MAX_HEADERS: usize = 64;
+//A whole request has to arrive within this, so a slow sender can not hold the connection
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+//Just enough HTTP/1.0 with JSON bodies for the card terminal adapter and the register server
+pub(crate) struct Client {
+    address: String,
+    //Sent as a bearer token, the register server refuses requests without it
+    key: Option<String>,
+}
+
+impl Client {
+    pub(crate) fn new(address: &str) -> Self {
+        Self {
+            address: address
+                .trim()
+                .trim_start_matches("http://")
+                .trim_end_matches('/')
+                .to_string(),
+            key: None,
+        }
+    }
+
+    pub(crate) fn with_key(self, key: &str) -> Self {
+        Self {
+            key: Some(key.to_string()),
+            ..self
+        }
+    }
+
+    pub(crate) async fn call(
+        &self,
+        method: &str,
+        path: &str,
+        body: Option<String>,
+    ) -> Result<String> {
+        tokio::time::timeout(TIMEOUT, self.send(method, path, body))
+            .await
+            .map_err(|_| Error::Timeout)?
+    }
+
+    //HTTP/1.0 so the response is simply everything until the connection closes
+    async fn send(&self, method: &str, path: &str, body: Option<String>) -> Result<String> {
+        let mut stream = TcpStream::connect(&self.address)
+            .await
+            .map_err(|e| Error::Io(e.kind()))?;
+        let body = body.unwrap_or_default();
+        let authorization = match &self.key {
+            Some(key) => format!("Authorization: Bearer {key}\r\n"),
+            None => String::new(),
+        };
+        stream
+            .write_all(
+                format!(
+                    "{method} {path} HTTP/1.0\r\nHost: {}\r\n{authorization}Content-Type: application/json\r\nContent-Length: {}\r\n\r\n{body}",
+                    self.address,
+                    body.len()
+                )
+                .as_bytes(),
+            )
+            .await
+            .map_err(|e| Error::Io(e.kind()))?;
+
+        let mut response = String::new();
+        stream
+            .read_to_string(&mut response)
+            .await
+            .map_err(|e| Error::Io(e.kind()))?;
+        let (head, body) = response
+            .split_once("\r\n\r\n")
+            .ok_or(Error::InvalidResponse)?;
+        match head.split_whitespace().nth(1).map(str::parse::<u16>) {
+            Some(Ok(200..=299)) => Ok(body.to_string()),
+            Some(Ok(status)) => Err(Error::Status(status)),
+            _ => Err(Error::InvalidResponse),
+        }
+    }
+}
+
+pub(crate) fn parse<T: DeserializeOwned>(body: &str) -> Result<T> {
+    serde_json::from_str(body).map_err(|e| Error::Json(Arc::new(e)))
+}
+
+//Request as read by the server, headers other than the length and key are ignored
+pub(crate) struct Request {
+    pub(crate) method: String,
+    pub(crate) path: String,
+    pub(crate) body: String,
+}
+
+pub(crate) async fn read_request(stream: &mut TcpStream, key: &str) -> Result<Request> {
+    tokio::time::timeout(READ_TIMEOUT, read(stream, key))
+        .await
+        .map_err(|_| Error::Timeout)?
+}
+
+//The key is checked before the body is read, so nothing large is kept for a stranger
+async fn read(stream: &mut TcpStream, key: &str) -> Result<Request> {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    read_line(&mut reader, &mut line).await?;
+    let mut parts = line.split_whitespace();
+    let (method, path) = match (parts.next(), parts.next()) {
+        (Some(method), Some(path)) => (method.to_string(), path.to_string()),
+        _ => return Err(Error::InvalidRequest),
+    };
+
+    let mut length = 0;
+    let mut authorized = false;
+    for headers in 0.. {
+        if headers == MAX_HEADERS {
+            return Err(Error::InvalidRequest);
+        }
+        line.clear();
+        read_line(&mut reader, &mut line).await?;
+        match line.trim_end().split_once(':') {
+            Some((name, value)) if name.eq_ignore_ascii_case("content-length") => {
+                length = value.trim().parse().map_err(|_| Error::InvalidRequest)?;
+            }
+            Some((name, value)) if name.eq_ignore_ascii_case("authorization") => {
+                authorized = value
+                    .trim()
+                    .strip_prefix("Bearer ")
+                    .is_some_and(|sent| same_key(sent, key));
+            }
+            Some(_) => (),
+            None => break,
+        }
+    }
+
+    if !authorized {
+        return Err(Error::Unauthorized);
+    }
+    if length > MAX_BODY {
+        return Err(Error::TooLarge);
+    }
+    let mut body = vec![0; length];
+    reader
+        .read_exact(&mut body)
+        .await
+        .map_err(|e| Error::Io(e.kind()))?;
+    Ok(Request {
+        method,
+        path,
+        body: String::from_utf8(body).map_err(|_| Error::InvalidRequest)?,
+    })
+}
+
+//A line without its end was cut off by the limit, or the connection closed halfway
+async fn read_line(reader: &mut BufReader<&mut TcpStream>, line: &mut String) -> Result<()> {
+    reader
+        .take(MAX_LINE)
+        .read_line(line)
+        .await
+        .map_err(|e| Error::Io(e.kind()))?;
+    match line.ends_with('\n') {
+        true => Ok(()),
+        false => Err(Error::InvalidRequest),
+    }
+}
+
+//Takes as long whichever byte differs, so the key can not be guessed one byte at a time
+fn same_key(sent: &str, key: &str) -> bool {
+    sent.len() == key.len()
+        && sent
+            .bytes()
+            .zip(key.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+pub(crate) async fn respond(stream: &mut TcpStream, status: u16, body: &str) -> Result<()> {
+    stream
+        .write_all(
+            format!(
+                "HTTP/1.0 {status} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{body}",
+                match status {
+                    200 => "OK",
+                    401 => "Unauthorized",
+                    413 => "Payload Too Large",
+                    _ => "Error",
+                },
+                body.len()
+            )
+            .as_bytes(),
+        )
+        .await
+        .map_err(|e| Error::Io(e.kind()))?;
+    stream.shutdown().await.map_err(|e| Error::Io(e.kind()))
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, Clone)]
+pub enum Error {
+    Timeout,
+    InvalidResponse,
+    InvalidRequest,
+    Unauthorized,
+    TooLarge,
+    Status(u16),
+    Io(std::io::ErrorKind),
+    Json(Arc<serde_json::Error>),
+}
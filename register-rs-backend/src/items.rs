@@ -1,21 +1,39 @@
-use crate::Result;
+use crate::{CONNECTION, Result, sync};
 use chrono::{DateTime, Local};
 use getset::{Getters, WithSetters};
 use rusqlite::params;
+use serde::{Deserialize, Serialize};
 
+pub mod alcohol;
+pub mod allergen;
 pub mod category;
+pub mod images;
+pub mod transfer;
 
+pub use alcohol::{Alcohol, AlcoholKind};
+pub use allergen::{Allergen, Allergens};
 pub use category::Category;
 
-#[derive(Default, Debug, Clone, PartialEq, Eq, Getters, WithSetters)]
+#[derive(Default, Debug, Clone, PartialEq, Eq, Getters, WithSetters, Serialize, Deserialize)]
 #[getset(get = "pub", set_with = "pub")]
 pub struct Item {
     name: String,
     price: i32,
+    //Used instead of price when a member is linked to the receipt
+    member_price: Option<i32>,
     available: Option<bool>,
     #[getset(skip)]
     special: bool,
     category: Category,
+    barcode: Option<String>,
+    hotkey: Option<char>,
+    #[getset(skip)]
+    archived: bool,
+    description: Option<String>,
+    allergens: Allergens,
+    //Id of the picture, read with images::get
+    image: Option<i64>,
+    alcohol: Option<Alcohol>,
 }
 
 impl Item {
@@ -27,27 +45,46 @@ impl Item {
         self.special
     }
 
+    pub fn is_archived(&self) -> bool {
+        self.archived
+    }
+
     pub(crate) fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Self> {
         Ok(Self {
             name: row.get("name").or_else(|_| row.get("item"))?,
             price: row.get("price")?,
+            member_price: row.get("member_price").unwrap_or_default(),
             available: row.get("available").ok(),
             special: row.get("special").unwrap_or(false),
             category: row.get("category").unwrap_or(Category::Other),
+            barcode: row.get("barcode").unwrap_or_default(),
+            hotkey: row
+                .get::<_, Option<String>>("hotkey")
+                .unwrap_or_default()
+                .and_then(|s| s.chars().next()),
+            archived: row.get("archived").unwrap_or(false),
+            description: row.get("description").unwrap_or_default(),
+            allergens: row.get("allergens").unwrap_or_default(),
+            image: row.get("image").unwrap_or_default(),
+            alcohol: Alcohol::from_row(row),
         })
     }
 
     pub async fn get_all() -> Result<Vec<Self>> {
+        remote!("/items");
         select!(
-            "SELECT name, price, available, special, category FROM menu
+            "SELECT name, price, available, special, category, barcode, hotkey, archived,
+                    description, allergens, image, alcohol_kind, volume, abv, member_price
+                FROM menu
                 ORDER BY
                     special ASC,
                     CASE category
                         WHEN 'alcohol' THEN 1
                         WHEN 'drink' THEN 2
                         WHEN 'food' THEN 3
-                        WHEN 'other' THEN 4
-                        ELSE 5
+                        WHEN 'ticket' THEN 4
+                        WHEN 'other' THEN 5
+                        ELSE 6
                     END,
                     name DESC",
             Self::from_row
@@ -55,8 +92,11 @@ impl Item {
     }
 
     pub async fn get_all_available() -> Result<Vec<Self>> {
+        remote!("/items/available");
         select!(
-            "SELECT name, price, special, category FROM menu
+            "SELECT name, price, special, category, barcode, hotkey, description, allergens,
+                    image, alcohol_kind, volume, abv, member_price
+                FROM menu
                 WHERE available=true
                 ORDER BY
                     special ASC,
@@ -64,46 +104,329 @@ impl Item {
                         WHEN 'alcohol' THEN 1
                         WHEN 'drink' THEN 2
                         WHEN 'food' THEN 3
-                        WHEN 'other' THEN 4
-                        ELSE 5
+                        WHEN 'ticket' THEN 4
+                        WHEN 'other' THEN 5
+                        ELSE 6
                     END,
                     name DESC",
             Self::from_row
         )
     }
 
-    pub async fn insert_sale(self, time: DateTime<Local>, amount: i32) -> Result<()> {
-        insert!(
-            "INSERT INTO receipt_item (receipt, item, amount, price) VALUES (?1, ?2, ?3, ?4)",
-            params![time, self.name, amount, self.price,]
+    //Pinned items on this register if there are any, otherwise the most sold items the last 30 days
+    pub async fn get_favorites() -> Result<Vec<Self>> {
+        Self::get_favorites_at(favorites_register()).await
+    }
+
+    pub(crate) async fn get_favorites_at(register: String) -> Result<Vec<Self>> {
+        remote!("/items/favorites", register);
+        let pinned: Vec<Self> = select!(
+            "SELECT name, price, special, category, barcode, hotkey, description, allergens,
+                    image, alcohol_kind, volume, abv, member_price
+                FROM menu
+                    INNER JOIN favorites ON favorites.item = menu.name
+                WHERE available=true AND favorites.register=?1
+                ORDER BY name DESC",
+            params![register],
+            Self::from_row,
+            _
         )?;
+        if !pinned.is_empty() {
+            return Ok(pinned);
+        }
+
+        select!(
+            "SELECT menu.name, menu.price, menu.special, menu.category, menu.barcode, menu.hotkey,
+                    menu.description, menu.allergens, menu.image, menu.alcohol_kind, menu.volume,
+                    menu.abv, menu.member_price
+                FROM menu
+                    INNER JOIN receipts_view ON receipts_view.item = menu.name
+                WHERE menu.available=true
+                    AND NOT menu.special
+                    AND receipts_view.time > date('now','-30 day')
+                GROUP BY menu.name
+                ORDER BY SUM(receipts_view.amount) DESC
+                LIMIT 5",
+            Self::from_row
+        )
+    }
+
+    //Names of the items pinned on this register
+    pub async fn get_pinned() -> Result<Vec<String>> {
+        Self::get_pinned_at(favorites_register()).await
+    }
+
+    pub(crate) async fn get_pinned_at(register: String) -> Result<Vec<String>> {
+        remote!("/items/pinned", register);
+        select!(
+            "SELECT item FROM favorites WHERE register=?1",
+            params![register],
+            |row| row.get("item"),
+            _
+        )
+    }
+
+    pub async fn change_favorite(self, favorite: bool) -> Result<()> {
+        self.change_favorite_at(favorites_register(), favorite)
+            .await
+    }
+
+    pub(crate) async fn change_favorite_at(self, register: String, favorite: bool) -> Result<()> {
+        remote!("/items/favorite", (self, register, favorite));
+        if favorite {
+            insert!(
+                "INSERT OR IGNORE INTO favorites (register, item) VALUES (?1, ?2)",
+                params![register, self.name]
+            )?;
+        } else {
+            insert!(
+                "DELETE FROM favorites WHERE register=?1 AND item=?2",
+                params![register, self.name]
+            )?;
+        }
+        Ok(())
+    }
+
+    //Whole menu with the time each item was last changed, compared when syncing registers
+    pub(crate) async fn get_synced() -> Result<Vec<(Self, Option<DateTime<Local>>)>> {
+        select!(
+            "SELECT name, price, available, special, category, barcode, hotkey, archived,
+                    description, allergens, image, alcohol_kind, volume, abv, member_price, modified
+                FROM menu
+                WHERE NOT special",
+            |row| Ok((Self::from_row(row)?, row.get("modified")?))
+        )
+    }
+
+    pub(crate) async fn get(name: &str) -> Result<Option<Self>> {
+        select!(
+            "SELECT name, price, available, special, category, barcode, hotkey, archived,
+                    description, allergens, image, alcohol_kind, volume, abv, member_price
+                FROM menu
+                WHERE name=?1",
+            params![name],
+            Self::from_row,
+            ..
+        )
+        .next()
+        .transpose()
+    }
+
+    //Part of the sale, see Receipt::insert_sale
+    pub(crate) fn insert_sale(
+        &self,
+        conn: &rusqlite::Connection,
+        time: DateTime<Local>,
+        amount: i32,
+    ) -> Result<()> {
+        conn.prepare_cached(
+            "INSERT INTO receipt_item (receipt, item, amount, price) VALUES (?1, ?2, ?3, ?4)",
+        )?
+        .execute(params![time, self.name, amount, self.price])?;
         Ok(())
     }
 
     pub async fn insert_new(self) -> Result<()> {
+        remote!("/items/new", self);
         insert!(
-            "INSERT INTO menu (name, price, available, category) 
-                VALUES (?1, ?2, true, ?3)",
-            params![self.name, self.price, self.category]
+            "INSERT INTO menu (name, price, available, category, barcode, hotkey, description,
+                    allergens, alcohol_kind, volume, abv, member_price) 
+                VALUES (?1, ?2, true, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            params![
+                self.name,
+                self.price,
+                self.category,
+                self.barcode,
+                self.hotkey.map(String::from),
+                self.description,
+                self.allergens,
+                self.alcohol.map(|a| a.kind),
+                self.alcohol.map(|a| a.volume),
+                self.alcohol.map(|a| a.abv),
+                self.member_price
+            ]
         )?;
-        Ok(())
+        sync::item_changed(&self.name, Some(&self.name)).await
     }
 
     pub async fn update(self, new: Self) -> Result<()> {
+        remote!("/items/update", (self, new));
         insert!(
-            "UPDATE menu SET name=?1, price=?2, category=?3 WHERE name=?4",
-            params![new.name, new.price, new.category, self.name]
+            "UPDATE menu SET name=?1, price=?2, category=?3, barcode=?4, hotkey=?5, description=?6,
+                    allergens=?7, alcohol_kind=?8, volume=?9, abv=?10, member_price=?11
+                WHERE name=?12",
+            params![
+                new.name,
+                new.price,
+                new.category,
+                new.barcode,
+                new.hotkey.map(String::from),
+                new.description,
+                new.allergens,
+                new.alcohol.map(|a| a.kind),
+                new.alcohol.map(|a| a.volume),
+                new.alcohol.map(|a| a.abv),
+                new.member_price,
+                self.name
+            ]
         )?;
-        Ok(())
+        //Stays pinned on every register under the new name
+        if self.name != new.name {
+            insert!(
+                "UPDATE favorites SET item=?1 WHERE item=?2",
+                params![new.name, self.name]
+            )?;
+        }
+        sync::item_changed(&self.name, Some(&new.name)).await
     }
 
+    //The picture is changed apart from the rest of the item, see images
+    pub async fn change_image(self, image: Option<Vec<u8>>) -> Result<()> {
+        remote!("/items/image/change", (self, image));
+        if let Some(bytes) = &image {
+            images::check(bytes)?;
+        }
+        {
+            let mut conn = CONNECTION
+                .get()
+                .ok_or(crate::Error::NotConnected)?
+                .lock()
+                .await;
+            let tx = conn.transaction()?;
+            images::replace(&tx, &self.name, image.as_deref())?;
+            tx.commit()?;
+        }
+        sync::item_changed(&self.name, Some(&self.name)).await
+    }
+
+    //Archived items can not be made available without being restored first
     pub async fn change_availability(self, availability: bool) -> Result<()> {
+        remote!("/items/availability", (self, availability));
         insert!(
-            "UPDATE menu SET available=?1 WHERE name=?2",
+            "UPDATE menu SET available=?1 WHERE name=?2 AND NOT archived",
             params![availability, self.name]
         )?;
-        Ok(())
+        sync::item_changed(&self.name, Some(&self.name)).await
+    }
+
+    //Archived items are kept for old receipts but hidden and never available. Their barcode and
+    //hotkey are freed so they can be given to other items
+    pub async fn change_archived(self, archived: bool) -> Result<()> {
+        remote!("/items/archived", (self, archived));
+        insert!(
+            "UPDATE menu SET archived=?1, available=available AND NOT ?1,
+                    barcode=IIF(?1, NULL, barcode), hotkey=IIF(?1, NULL, hotkey)
+                WHERE name=?2",
+            params![archived, self.name]
+        )?;
+        sync::item_changed(&self.name, Some(&self.name)).await
+    }
+
+    //Only items that have never been sold can be deleted, others have to be archived
+    pub async fn delete(self) -> Result<()> {
+        remote!("/items/delete", self);
+        let deleted = insert!(
+            "DELETE FROM menu
+                WHERE name=?1
+                    AND NOT special
+                    AND NOT EXISTS (SELECT 1 FROM receipt_item WHERE item=?1)",
+            params![self.name]
+        )?;
+        if deleted == 0 {
+            match Self::get(&self.name).await? {
+                Some(item) if item.special => Err(crate::Error::SpecialItem),
+                _ => Err(crate::Error::ItemInUse),
+            }
+        } else {
+            insert!("DELETE FROM favorites WHERE item=?1", params![self.name])?;
+            images::remove_unused().await?;
+            sync::item_changed(&self.name, None).await
+        }
     }
+
+    //Changed on another register, found by the name it had there so renames are kept. The picture
+    //is sent along since the ids differ between the databases
+    pub(crate) async fn store(
+        self,
+        name: &str,
+        modified: DateTime<Local>,
+        image: Option<Vec<u8>>,
+    ) -> Result<()> {
+        //Sent by another register, so checked again before it is decoded anywhere
+        if let Some(bytes) = &image {
+            images::check(bytes)?;
+        }
+        if self.name != name {
+            insert!(
+                "UPDATE OR IGNORE menu SET name=?1 WHERE name=?2 AND NOT special",
+                params![self.name, name]
+            )?;
+            insert!(
+                "UPDATE OR IGNORE favorites SET item=?1 WHERE item=?2",
+                params![self.name, name]
+            )?;
+        }
+        insert!(
+            "INSERT INTO menu (name, price, available, category, barcode, hotkey, archived,
+                    description, allergens, alcohol_kind, volume, abv, member_price, modified)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)
+                ON CONFLICT(name) DO UPDATE SET price=excluded.price,
+                    available=excluded.available, category=excluded.category,
+                    barcode=excluded.barcode, hotkey=excluded.hotkey, archived=excluded.archived,
+                    description=excluded.description, allergens=excluded.allergens,
+                    alcohol_kind=excluded.alcohol_kind, volume=excluded.volume, abv=excluded.abv,
+                    member_price=excluded.member_price, modified=excluded.modified
+                WHERE NOT special",
+            params![
+                self.name,
+                self.price,
+                self.available.unwrap_or(true),
+                self.category,
+                self.barcode,
+                self.hotkey.map(String::from),
+                self.archived,
+                self.description,
+                self.allergens,
+                self.alcohol.map(|a| a.kind),
+                self.alcohol.map(|a| a.volume),
+                self.alcohol.map(|a| a.abv),
+                self.member_price,
+                modified
+            ]
+        )?;
+        let conn = CONNECTION
+            .get()
+            .ok_or(crate::Error::NotConnected)?
+            .lock()
+            .await;
+        images::replace(&conn, &self.name, image.as_deref())
+    }
+
+    //Deleted on another register, archived instead if it has been sold here
+    pub(crate) async fn remove(name: &str) -> Result<()> {
+        insert!(
+            "DELETE FROM menu
+                WHERE name=?1
+                    AND NOT special
+                    AND NOT EXISTS (SELECT 1 FROM receipt_item WHERE item=?1)",
+            params![name]
+        )?;
+        insert!(
+            "UPDATE menu SET archived=true, available=false, barcode=NULL, hotkey=NULL
+                WHERE name=?1 AND NOT special",
+            params![name]
+        )?;
+        insert!(
+            "DELETE FROM favorites WHERE item=?1 AND NOT EXISTS (SELECT 1 FROM menu WHERE name=?1)",
+            params![name]
+        )?;
+        images::remove_unused().await
+    }
+}
+
+//Favorites are kept per register, registers without a name share the same ones
+fn favorites_register() -> String {
+    crate::receipts::REGISTER.get().cloned().unwrap_or_default()
 }
 
 impl std::hash::Hash for Item {
@@ -0,0 +1,71 @@
+use {
+    rusqlite::types::{FromSql, FromSqlError, ToSql, ToSqlOutput, ValueRef},
+    serde::{Deserialize, Serialize},
+    strum::{Display, VariantArray},
+};
+
+//Groups used when reporting sales to the licensing authority
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, VariantArray, Display, Serialize, Deserialize,
+)]
+pub enum AlcoholKind {
+    #[strum(to_string = "Öl")]
+    Beer,
+    #[strum(to_string = "Vin")]
+    Wine,
+    #[strum(to_string = "Sprit")]
+    Spirits,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Alcohol {
+    pub kind: AlcoholKind,
+    //Centiliters per serving
+    pub volume: i32,
+    //Per mille alcohol by volume, 52 is 5.2%
+    pub abv: i32,
+}
+
+impl Alcohol {
+    //None unless all columns are selected and set
+    pub(crate) fn from_row(row: &rusqlite::Row) -> Option<Self> {
+        Some(Self {
+            kind: row.get::<_, Option<_>>("alcohol_kind").ok()??,
+            volume: row.get::<_, Option<_>>("volume").ok()??,
+            abv: row.get::<_, Option<_>>("abv").ok()??,
+        })
+    }
+
+    pub fn liters(&self, amount: i32) -> f64 {
+        (self.volume * amount) as f64 / 100.0
+    }
+
+    pub fn pure_liters(&self, amount: i32) -> f64 {
+        self.liters(amount) * self.abv as f64 / 1000.0
+    }
+
+    pub fn percentage(&self) -> f64 {
+        self.abv as f64 / 10.0
+    }
+}
+
+impl FromSql for AlcoholKind {
+    fn column_result(value: ValueRef<'_>) -> rusqlite::types::FromSqlResult<Self> {
+        match value {
+            ValueRef::Text(b"beer") => Ok(Self::Beer),
+            ValueRef::Text(b"wine") => Ok(Self::Wine),
+            ValueRef::Text(b"spirits") => Ok(Self::Spirits),
+            _ => Err(FromSqlError::InvalidType),
+        }
+    }
+}
+
+impl ToSql for AlcoholKind {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::Borrowed(ValueRef::Text(match self {
+            Self::Beer => b"beer",
+            Self::Wine => b"wine",
+            Self::Spirits => b"spirits",
+        })))
+    }
+}
@@ -0,0 +1,91 @@
+use {
+    rusqlite::types::{FromSql, FromSqlResult, ToSql, ToSqlOutput, ValueRef},
+    serde::{Deserialize, Serialize},
+    strum::{Display, VariantArray},
+};
+
+//The 14 allergens food businesses have to declare within the EU
+#[derive(Debug, Clone, Copy, PartialEq, Eq, VariantArray, Display)]
+pub enum Allergen {
+    Gluten,
+    #[strum(to_string = "Laktos")]
+    Lactose,
+    #[strum(to_string = "Nötter")]
+    Nuts,
+    #[strum(to_string = "Jordnötter")]
+    Peanuts,
+    #[strum(to_string = "Ägg")]
+    Egg,
+    #[strum(to_string = "Fisk")]
+    Fish,
+    #[strum(to_string = "Kräftdjur")]
+    Crustaceans,
+    #[strum(to_string = "Blötdjur")]
+    Molluscs,
+    #[strum(to_string = "Soja")]
+    Soy,
+    #[strum(to_string = "Selleri")]
+    Celery,
+    #[strum(to_string = "Senap")]
+    Mustard,
+    #[strum(to_string = "Sesam")]
+    Sesame,
+    #[strum(to_string = "Lupin")]
+    Lupin,
+    #[strum(to_string = "Sulfiter")]
+    Sulphites,
+}
+
+impl Allergen {
+    fn bit(self) -> u16 {
+        1 << self as u16
+    }
+}
+
+//Stored as a bitmask in a single column
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Allergens(u16);
+
+impl Allergens {
+    pub fn contains(&self, allergen: Allergen) -> bool {
+        self.0 & allergen.bit() != 0
+    }
+
+    pub fn with(self, allergen: Allergen, present: bool) -> Self {
+        if present {
+            Self(self.0 | allergen.bit())
+        } else {
+            Self(self.0 & !allergen.bit())
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Allergen> + '_ {
+        Allergen::VARIANTS
+            .iter()
+            .copied()
+            .filter(|a| self.contains(*a))
+    }
+}
+
+impl std::fmt::Display for Allergens {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let names = self.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+        write!(f, "{}", names.join(", "))
+    }
+}
+
+impl FromSql for Allergens {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        u16::column_result(value).map(Self)
+    }
+}
+
+impl ToSql for Allergens {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(self.0))
+    }
+}
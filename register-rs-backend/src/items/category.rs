@@ -1,9 +1,17 @@
 use {
-    rusqlite::types::{FromSql, FromSqlError, ToSql, ToSqlOutput, ValueRef},
+    crate::Result,
+    rusqlite::{
+        params,
+        types::{FromSql, FromSqlError, ToSql, ToSqlOutput, ValueRef},
+    },
+    serde::{Deserialize, Serialize},
     strum::{Display, VariantArray},
 };
 
-#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, VariantArray, Display)]
+#[derive(
+    Default, Debug, Clone, Copy, PartialEq, Eq, VariantArray, Display, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
 pub enum Category {
     #[strum(to_string = "Alkohol")]
     Alcohol,
@@ -11,17 +19,49 @@ pub enum Category {
     Drink,
     #[strum(to_string = "Mat")]
     Food,
+    #[strum(to_string = "Biljett")]
+    Ticket,
     #[default]
     #[strum(to_string = "Övrigt")]
     Other,
 }
 
+impl Category {
+    //Categories where the bartender has to confirm that ID was checked
+    pub async fn get_age_checked() -> Result<Vec<Self>> {
+        remote!("/categories/age_checked");
+        select!(
+            "SELECT category FROM categories WHERE age_check",
+            |row| row.get("category")
+        )
+    }
+
+    //Swedish VAT in percent, food and non-alcoholic drinks are reduced and tickets count as culture
+    pub fn vat(&self) -> i32 {
+        match self {
+            Self::Alcohol | Self::Other => 25,
+            Self::Drink | Self::Food => 12,
+            Self::Ticket => 6,
+        }
+    }
+
+    pub async fn change_age_check(self, age_check: bool) -> Result<()> {
+        remote!("/categories/age_check", (self, age_check));
+        insert!(
+            "UPDATE categories SET age_check=?1 WHERE category=?2",
+            params![age_check, self]
+        )?;
+        Ok(())
+    }
+}
+
 impl FromSql for Category {
     fn column_result(value: ValueRef<'_>) -> rusqlite::types::FromSqlResult<Self> {
         match value {
             ValueRef::Text(b"alcohol") => Ok(Self::Alcohol),
             ValueRef::Text(b"drink") => Ok(Self::Drink),
             ValueRef::Text(b"food") => Ok(Self::Food),
+            ValueRef::Text(b"ticket") => Ok(Self::Ticket),
             ValueRef::Text(b"other") => Ok(Self::Other),
             _ => Err(FromSqlError::InvalidType),
         }
@@ -34,6 +74,7 @@ impl ToSql for Category {
             Self::Alcohol => b"alcohol",
             Self::Drink => b"drink",
             Self::Food => b"food",
+            Self::Ticket => b"ticket",
             Self::Other => b"other",
         })))
     }
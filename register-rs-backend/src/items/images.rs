@@ -0,0 +1,75 @@
+use {
+    crate::{Error, Result},
+    image::io::Reader,
+    rusqlite::{Connection, OptionalExtension, params},
+    std::io::Cursor,
+};
+
+//Only shown next to the item on the menu, larger files are refused
+pub const MAX_SIZE: usize = 512 * 1024;
+//A small file can claim any size, so this is read from the header before it is decoded
+pub const MAX_PIXELS: u64 = 4096 * 4096;
+
+//Has to be a picture that can be shown, checked before it is stored
+pub fn check(bytes: &[u8]) -> Result<()> {
+    if bytes.len() > MAX_SIZE {
+        return Err(Error::ImageTooLarge);
+    }
+    let (width, height) = Reader::new(Cursor::new(bytes))
+        .with_guessed_format()
+        .map_err(|_| Error::InvalidImage)?
+        .into_dimensions()
+        .map_err(|_| Error::InvalidImage)?;
+    if width as u64 * height as u64 > MAX_PIXELS {
+        return Err(Error::ImageTooLarge);
+    }
+    image::load_from_memory(bytes).map_err(|_| Error::InvalidImage)?;
+    Ok(())
+}
+
+//Read apart from the items so the menu stays small, the ids change with the picture so they can
+//be kept once read
+pub async fn get(id: i64) -> Result<Option<Vec<u8>>> {
+    remote!("/items/image", id);
+    select!(
+        "SELECT image FROM images WHERE id=?1",
+        params![id],
+        |row| row.get("image"),
+        ..
+    )
+    .next()
+    .transpose()
+}
+
+//Stored under a new id and the old picture is dropped
+pub(crate) fn replace(conn: &Connection, item: &str, bytes: Option<&[u8]>) -> Result<()> {
+    let old: Option<i64> = conn
+        .prepare_cached("SELECT image FROM menu WHERE name=?1")?
+        .query_row(params![item], |row| row.get("image"))
+        .optional()?
+        .flatten();
+    let new = match bytes {
+        Some(bytes) => {
+            conn.prepare_cached("INSERT INTO images (image) VALUES (?1)")?
+                .execute(params![bytes])?;
+            Some(conn.last_insert_rowid())
+        }
+        None => None,
+    };
+    conn.prepare_cached("UPDATE menu SET image=?1 WHERE name=?2")?
+        .execute(params![new, item])?;
+    if let Some(old) = old {
+        conn.prepare_cached("DELETE FROM images WHERE id=?1")?
+            .execute(params![old])?;
+    }
+    Ok(())
+}
+
+//Left behind by deleted items
+pub(crate) async fn remove_unused() -> Result<()> {
+    insert!(
+        "DELETE FROM images WHERE id NOT IN (SELECT image FROM menu WHERE image IS NOT NULL)",
+        []
+    )?;
+    Ok(())
+}
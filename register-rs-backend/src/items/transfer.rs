@@ -0,0 +1,250 @@
+use {
+    super::{Category, Item},
+    crate::{CONNECTION, settings::Settings, sync},
+    rusqlite::params,
+    serde::{Deserialize, Serialize},
+    std::{
+        fs::File,
+        path::{Path, PathBuf},
+        sync::Arc,
+    },
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Csv,
+    Json,
+}
+
+impl Format {
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self> {
+        match path.as_ref().extension().and_then(|ext| ext.to_str()) {
+            Some("csv") => Ok(Self::Csv),
+            Some("json") => Ok(Self::Json),
+            _ => Err(Error::UnknownFormat),
+        }
+    }
+
+    fn extension(&self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Json => "json",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Record {
+    name: String,
+    price: i32,
+    #[serde(default)]
+    category: Category,
+    #[serde(default = "default_available")]
+    available: bool,
+    #[serde(default)]
+    special: bool,
+}
+
+fn default_available() -> bool {
+    true
+}
+
+impl From<&Item> for Record {
+    fn from(item: &Item) -> Self {
+        Self {
+            name: item.name.clone(),
+            price: item.price,
+            category: item.category,
+            available: item.available.unwrap_or(true),
+            special: item.special,
+        }
+    }
+}
+
+impl From<Record> for Item {
+    fn from(record: Record) -> Self {
+        Self {
+            name: record.name,
+            price: record.price,
+            available: Some(record.available),
+            special: record.special,
+            category: record.category,
+            ..Default::default()
+        }
+    }
+}
+
+pub fn read(path: impl AsRef<Path>) -> Result<Vec<Item>> {
+    let format = Format::from_path(&path)?;
+    let file = File::open(path).map_err(|e| Error::Io(e.kind()))?;
+    let records: Vec<Record> = match format {
+        Format::Csv => csv::Reader::from_reader(file)
+            .deserialize()
+            .collect::<std::result::Result<_, _>>()
+            .map_err(|e| Error::Csv(Arc::new(e)))?,
+        Format::Json => serde_json::from_reader(file).map_err(|e| Error::Json(Arc::new(e)))?,
+    };
+    Ok(records.into_iter().map(Item::from).collect())
+}
+
+fn write(path: impl AsRef<Path>, format: Format, items: &[Item]) -> Result<()> {
+    let file = File::create(path).map_err(|e| Error::Io(e.kind()))?;
+    let records = items.iter().map(Record::from);
+    match format {
+        Format::Csv => {
+            let mut writer = csv::Writer::from_writer(file);
+            for record in records {
+                writer
+                    .serialize(record)
+                    .map_err(|e| Error::Csv(Arc::new(e)))?;
+            }
+            writer.flush().map_err(|e| Error::Io(e.kind()))
+        }
+        Format::Json => serde_json::to_writer_pretty(file, &records.collect::<Vec<_>>())
+            .map_err(|e| Error::Json(Arc::new(e))),
+    }
+}
+
+fn export_dir(settings: &Settings) -> Result<PathBuf> {
+    match settings.export_dir() {
+        Some(dir) => menu_dir(PathBuf::from(dir)),
+        None => default_export_dir(),
+    }
+}
+
+fn menu_dir(mut path: PathBuf) -> Result<PathBuf> {
+    path.push("menu");
+    if let Err(e) = std::fs::create_dir_all(&path) {
+        match e.kind() {
+            std::io::ErrorKind::AlreadyExists => (),
+            ek => return Err(Error::Io(ek)),
+        }
+    }
+    Ok(path)
+}
+
+#[cfg(not(debug_assertions))]
+fn default_export_dir() -> Result<PathBuf> {
+    menu_dir(dirs::document_dir().ok_or(Error::NoPath)?)
+}
+
+#[cfg(debug_assertions)]
+fn default_export_dir() -> Result<PathBuf> {
+    Ok(PathBuf::from("."))
+}
+
+//Archived items are left out
+pub async fn export(format: Format) -> crate::Result<PathBuf> {
+    let items = Item::get_all()
+        .await?
+        .into_iter()
+        .filter(|item| !item.is_archived())
+        .collect::<Vec<_>>();
+
+    let mut path = export_dir(&Settings::get().await?)?;
+    path.push(format!(
+        "menu_{}.{}",
+        chrono::Local::now().format("%F"),
+        format.extension()
+    ));
+    write(&path, format, &items)?;
+    Ok(path)
+}
+
+//What an import would do to the current menu, items are matched by name. Archived items are
+//skipped, they have to be restored in the manager before an import can change them. Special
+//items are used by the register itself, so they are never changed or made by an import
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Import {
+    pub create: Vec<Item>,
+    pub update: Vec<Item>,
+    pub untouched: Vec<Item>,
+    pub archived: Vec<Item>,
+    pub special: Vec<Item>,
+}
+
+impl Import {
+    pub fn new(current: &[Item], incoming: Vec<Item>) -> Self {
+        incoming
+            .into_iter()
+            .fold(Self::default(), |mut import, item| {
+                match current.iter().find(|c| c.name == item.name) {
+                    None => import.create.push(item),
+                    Some(c) if c.special => import.special.push(item),
+                    Some(c) if c.archived => import.archived.push(item),
+                    Some(c)
+                        if (c.price, c.category, c.available)
+                            == (item.price, item.category, item.available) =>
+                    {
+                        import.untouched.push(item)
+                    }
+                    Some(_) => import.update.push(item),
+                }
+                import
+            })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.create.is_empty() && self.update.is_empty()
+    }
+
+    //All or nothing
+    pub async fn apply(self) -> crate::Result<()> {
+        remote!("/items/import", self);
+        //Sent to the other registers once stored, as when editing the items one by one
+        let names: Vec<String> = self
+            .create
+            .iter()
+            .chain(&self.update)
+            .map(|item| item.name.clone())
+            .collect();
+        {
+            let mut conn = CONNECTION
+                .get()
+                .ok_or(crate::Error::NotConnected)?
+                .lock()
+                .await;
+            let tx = conn.transaction()?;
+            for item in self.create {
+                tx.prepare_cached(
+                    "INSERT INTO menu (name, price, available, special, category)
+                        VALUES (?1, ?2, ?3, FALSE, ?4)",
+                )?
+                .execute(params![
+                    item.name,
+                    item.price,
+                    item.available.unwrap_or(true),
+                    item.category
+                ])?;
+            }
+            for item in self.update {
+                tx.prepare_cached(
+                    "UPDATE menu SET price=?1, available=?2, category=?3
+                        WHERE name=?4 AND NOT archived AND NOT special",
+                )?
+                .execute(params![
+                    item.price,
+                    item.available.unwrap_or(true),
+                    item.category,
+                    item.name
+                ])?;
+            }
+            tx.commit()?;
+        }
+        for name in names {
+            sync::item_changed(&name, Some(&name)).await?;
+        }
+        Ok(())
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, Clone)]
+pub enum Error {
+    NoPath,
+    UnknownFormat,
+    Io(std::io::ErrorKind),
+    Csv(Arc<csv::Error>),
+    Json(Arc<serde_json::Error>),
+}
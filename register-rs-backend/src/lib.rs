@@ -1,6 +1,7 @@
 use giftwrap::Wrap;
 use rusqlite::Connection;
 use rusqlite_migration::{M, Migrations};
+use std::hash::{BuildHasher, Hasher};
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, LazyLock, OnceLock};
 use tokio::sync::Mutex;
@@ -48,9 +49,35 @@ macro_rules! insert {
     };
 }
 
+//Runs the function on the register server instead when connected to one, the arguments are sent as
+//JSON and the server calls the same function on its own database
+macro_rules! remote {
+    ($path:literal) => {
+        remote!($path, ())
+    };
+
+    ($path:literal, $args:expr) => {
+        if $crate::remote::is_connected() {
+            return $crate::remote::call($path, &$args).await;
+        }
+    };
+}
+
+pub mod accounts;
+pub mod events;
+pub mod http;
 pub mod items;
+pub mod members;
+pub mod orders;
+pub mod print_queue;
 pub mod receipts;
+pub mod remote;
+pub mod settings;
 pub mod summary;
+pub mod sync;
+pub mod terminal;
+pub mod tickets;
+pub mod vouchers;
 
 static MIGRATIONS: LazyLock<Migrations<'static>> = LazyLock::new(|| {
     Migrations::new(vec![
@@ -78,6 +105,200 @@ static MIGRATIONS: LazyLock<Migrations<'static>> = LazyLock::new(|| {
                     WHERE m.name = item AND m.special
             "#,
         ),
+        M::up(
+            r#"ALTER TABLE menu ADD COLUMN barcode TEXT;
+               CREATE UNIQUE INDEX IF NOT EXISTS menu_barcode ON menu(barcode);
+            "#,
+        ),
+        M::up(
+            r#"ALTER TABLE menu ADD COLUMN hotkey TEXT;
+               CREATE UNIQUE INDEX IF NOT EXISTS menu_hotkey ON menu(hotkey);
+            "#,
+        ),
+        M::up("ALTER TABLE menu ADD COLUMN favorite BOOLEAN DEFAULT false NOT NULL;"),
+        M::up("ALTER TABLE menu ADD COLUMN archived BOOLEAN DEFAULT false NOT NULL;"),
+        M::up(
+            r#"ALTER TABLE menu ADD COLUMN description TEXT;
+               ALTER TABLE menu ADD COLUMN allergens INTEGER DEFAULT 0 NOT NULL;
+               ALTER TABLE menu ADD COLUMN image BLOB;
+            "#,
+        ),
+        M::up(
+            r#"CREATE TABLE IF NOT EXISTS categories (
+                   category TEXT NOT NULL PRIMARY KEY,
+                   age_check BOOLEAN DEFAULT false NOT NULL
+               );
+               INSERT OR IGNORE INTO categories (category, age_check)
+                   VALUES
+                       ('alcohol', true),
+                       ('drink', false),
+                       ('food', false),
+                       ('other', false);
+               ALTER TABLE receipts ADD COLUMN age_verified BOOLEAN DEFAULT false NOT NULL;
+            "#,
+        ),
+        M::up(
+            r#"ALTER TABLE menu ADD COLUMN alcohol_kind TEXT;
+               ALTER TABLE menu ADD COLUMN volume INTEGER;
+               ALTER TABLE menu ADD COLUMN abv INTEGER;
+            "#,
+        ),
+        M::up(
+            r#"CREATE TABLE IF NOT EXISTS events (
+                   id INTEGER PRIMARY KEY,
+                   name TEXT NOT NULL,
+                   start_time DATETIME NOT NULL,
+                   end_time DATETIME NOT NULL,
+                   active BOOLEAN DEFAULT false NOT NULL
+               );
+               ALTER TABLE receipts ADD COLUMN event INTEGER REFERENCES events(id);
+               DROP VIEW receipts_view;
+               CREATE VIEW IF NOT EXISTS receipts_view AS
+                   SELECT receipts.time, receipt_item.item, receipt_item.amount, receipt_item.price, menu.special, receipts.method, receipts.event
+                   FROM receipts
+                       INNER JOIN receipt_item ON receipts.time = receipt_item.receipt
+                       INNER JOIN menu ON receipt_item.item = menu.name;
+            "#,
+        ),
+        M::up(
+            r#"CREATE TABLE IF NOT EXISTS members (
+                   number TEXT NOT NULL PRIMARY KEY,
+                   name TEXT NOT NULL,
+                   valid_until DATE NOT NULL
+               );
+               ALTER TABLE menu ADD COLUMN member_price INTEGER;
+               ALTER TABLE receipts ADD COLUMN member TEXT REFERENCES members(number);
+               DROP VIEW receipts_view;
+               CREATE VIEW IF NOT EXISTS receipts_view AS
+                   SELECT receipts.time, receipt_item.item, receipt_item.amount, receipt_item.price, menu.special, receipts.method, receipts.event, receipts.member
+                   FROM receipts
+                       INNER JOIN receipt_item ON receipts.time = receipt_item.receipt
+                       INNER JOIN menu ON receipt_item.item = menu.name;
+            "#,
+        ),
+        M::up(
+            r#"CREATE TABLE IF NOT EXISTS accounts (
+                   card TEXT NOT NULL PRIMARY KEY,
+                   holder TEXT NOT NULL
+               );
+               CREATE TABLE IF NOT EXISTS account_ledger (
+                   id INTEGER PRIMARY KEY,
+                   card TEXT NOT NULL REFERENCES accounts(card),
+                   time DATETIME NOT NULL,
+                   amount INTEGER NOT NULL
+               );
+               ALTER TABLE receipts ADD COLUMN account TEXT REFERENCES accounts(card);
+               INSERT OR IGNORE INTO menu (name, price, available, special)
+                   VALUES ('Saldo', 1, true, true);
+            "#,
+        ),
+        M::up(
+            r#"CREATE TABLE IF NOT EXISTS vouchers (
+                   code TEXT NOT NULL PRIMARY KEY,
+                   value INTEGER NOT NULL,
+                   issued DATETIME NOT NULL,
+                   expires DATE NOT NULL
+               );
+               CREATE TABLE IF NOT EXISTS voucher_redemptions (
+                   id INTEGER PRIMARY KEY,
+                   code TEXT NOT NULL REFERENCES vouchers(code),
+                   time DATETIME NOT NULL,
+                   amount INTEGER NOT NULL
+               );
+               ALTER TABLE receipts ADD COLUMN voucher TEXT REFERENCES vouchers(code);
+            "#,
+        ),
+        M::up(
+            r#"INSERT OR IGNORE INTO categories (category, age_check) VALUES ('ticket', false);
+               CREATE TABLE IF NOT EXISTS tickets (
+                   number TEXT NOT NULL PRIMARY KEY,
+                   item TEXT NOT NULL REFERENCES menu(name),
+                   receipt DATETIME NOT NULL REFERENCES receipts(time),
+                   event INTEGER REFERENCES events(id),
+                   used DATETIME
+               );
+            "#,
+        ),
+        M::up(
+            r#"CREATE TABLE IF NOT EXISTS settings (
+                   key TEXT NOT NULL PRIMARY KEY,
+                   value TEXT NOT NULL
+               );
+            "#,
+        ),
+        M::up(
+            r#"ALTER TABLE receipts ADD COLUMN number INTEGER;
+               UPDATE receipts SET number = (
+                   SELECT COUNT(*) FROM receipts AS r WHERE r.time <= receipts.time
+               );
+               CREATE UNIQUE INDEX IF NOT EXISTS receipts_number ON receipts(number);
+               DROP VIEW receipts_view;
+               CREATE VIEW IF NOT EXISTS receipts_view AS
+                   SELECT receipts.time, receipts.number, receipt_item.item, receipt_item.amount, receipt_item.price, menu.special, menu.category, receipts.method, receipts.event, receipts.member
+                   FROM receipts
+                       INNER JOIN receipt_item ON receipts.time = receipt_item.receipt
+                       INNER JOIN menu ON receipt_item.item = menu.name;
+            "#,
+        ),
+        M::up(
+            r#"CREATE TABLE IF NOT EXISTS orders (
+                   id INTEGER PRIMARY KEY,
+                   number INTEGER NOT NULL,
+                   receipt DATETIME NOT NULL UNIQUE REFERENCES receipts(time)
+               );
+            "#,
+        ),
+        M::up("ALTER TABLE orders ADD COLUMN status TEXT DEFAULT 'open' NOT NULL;"),
+        M::up(
+            r#"ALTER TABLE receipts ADD COLUMN register TEXT;
+               DROP VIEW receipts_view;
+               CREATE VIEW IF NOT EXISTS receipts_view AS
+                   SELECT receipts.time, receipts.number, receipt_item.item, receipt_item.amount, receipt_item.price, menu.special, menu.category, receipts.method, receipts.event, receipts.member, receipts.register
+                   FROM receipts
+                       INNER JOIN receipt_item ON receipts.time = receipt_item.receipt
+                       INNER JOIN menu ON receipt_item.item = menu.name;
+            "#,
+        ),
+        M::up(
+            r#"CREATE TABLE IF NOT EXISTS outbox (
+                   id INTEGER PRIMARY KEY,
+                   kind TEXT NOT NULL,
+                   payload TEXT NOT NULL
+               );
+               ALTER TABLE menu ADD COLUMN modified DATETIME;
+            "#,
+        ),
+        M::up(
+            r#"DROP INDEX receipts_number;
+               CREATE UNIQUE INDEX IF NOT EXISTS receipts_number
+                   ON receipts(IFNULL(register, ''), number);
+            "#,
+        ),
+        M::up(r#"ALTER TABLE outbox ADD COLUMN rejected TEXT;"#),
+        M::up(
+            r#"CREATE TABLE IF NOT EXISTS favorites (
+                   register TEXT NOT NULL,
+                   item TEXT NOT NULL,
+                   PRIMARY KEY (register, item)
+               );
+               INSERT INTO favorites (register, item) SELECT '', name FROM menu WHERE favorite;
+               ALTER TABLE menu DROP COLUMN favorite;
+            "#,
+        ),
+        M::up(
+            r#"CREATE TABLE IF NOT EXISTS images (
+                   id INTEGER PRIMARY KEY,
+                   image BLOB NOT NULL
+               );
+               CREATE TEMP TABLE moved AS SELECT name, image FROM menu WHERE image IS NOT NULL;
+               ALTER TABLE menu DROP COLUMN image;
+               ALTER TABLE menu ADD COLUMN image INTEGER REFERENCES images(id);
+               INSERT INTO images (id, image) SELECT rowid, image FROM moved;
+               UPDATE menu SET image=(SELECT rowid FROM moved WHERE moved.name=menu.name);
+               DROP TABLE moved;
+            "#,
+        ),
+        M::up("UPDATE menu SET barcode=NULL, hotkey=NULL WHERE archived;"),
     ])
 });
 
@@ -91,6 +312,43 @@ pub fn connect<P: AsRef<Path>>(path: P) -> Result<()> {
         .map_err(|_| Error::AlreadyConnected)
 }
 
+//Client mode, the menu, receipts and summaries are read from and stored on the register server
+//while settings stay in the local database
+pub fn connect_server(address: &str, key: &str) -> Result<()> {
+    remote::SERVER
+        .set(http::Client::new(address).with_key(key))
+        .map_err(|_| Error::AlreadyConnected)
+}
+
+//Stored on every receipt so sales can be told apart when several registers share a server
+pub fn set_register(id: String) -> Result<()> {
+    receipts::REGISTER
+        .set(id)
+        .map_err(|_| Error::RegisterAlreadySet)
+}
+
+//Letters and digits that can not be mistaken for each other
+const CODE_CHARS: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+
+//Codes printed on vouchers and tickets, uniqueness is up to the primary key
+pub(crate) fn random_code(len: usize) -> String {
+    //RandomState is seeded per process and the time makes codes differ within it
+    let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+    hasher.write_i64(
+        chrono::Local::now()
+            .timestamp_nanos_opt()
+            .unwrap_or_default(),
+    );
+    let mut n = hasher.finish();
+    (0..len)
+        .map(|_| {
+            let c = CODE_CHARS[(n % CODE_CHARS.len() as u64) as usize];
+            n /= CODE_CHARS.len() as u64;
+            c as char
+        })
+        .collect()
+}
+
 pub fn set_receipt_path(path: PathBuf) -> Result<()> {
     receipts::print::RECEIPT_PATH
         .set(path)
@@ -109,10 +367,50 @@ pub enum Error {
     PrintError(Arc<receipts::print::Error>),
     #[giftwrap(wrapDepth = 0)]
     SummaryError(summary::save::Error),
+    #[giftwrap(wrapDepth = 0)]
+    TransferError(items::transfer::Error),
+    #[giftwrap(wrapDepth = 0)]
+    HttpError(http::Error),
     #[giftwrap(noWrap = true)]
     AlreadyConnected,
     #[giftwrap(noWrap = true)]
     NotConnected,
     #[giftwrap(noWrap = true)]
     PathAlreadySet,
+    #[giftwrap(noWrap = true)]
+    RegisterAlreadySet,
+    //Syncing without a name for the register
+    #[giftwrap(noWrap = true)]
+    NoRegister,
+    #[giftwrap(noWrap = true)]
+    ItemInUse,
+    //Special items such as discounts and top-ups are used by the register itself
+    #[giftwrap(noWrap = true)]
+    SpecialItem,
+    //Larger than items::images::MAX_SIZE or MAX_PIXELS
+    #[giftwrap(noWrap = true)]
+    ImageTooLarge,
+    //Not a picture that can be shown
+    #[giftwrap(noWrap = true)]
+    InvalidImage,
+    #[giftwrap(noWrap = true)]
+    InsufficientFunds,
+    //Zero or negative amount paid with or put on a card or voucher
+    #[giftwrap(noWrap = true)]
+    InvalidAmount,
+    #[giftwrap(noWrap = true)]
+    NoAccount,
+    #[giftwrap(noWrap = true)]
+    NoVoucher,
+    #[giftwrap(noWrap = true)]
+    VoucherNotValid,
+    //Paid with or topping up a card or voucher on a register that syncs
+    #[giftwrap(noWrap = true)]
+    NotWhileSyncing,
+    //Change the register server will never store, e.g. it breaks a constraint or can not be read
+    #[giftwrap(noWrap = true)]
+    Refused(String),
+    //Any other error on the register server
+    #[giftwrap(noWrap = true)]
+    Remote(String),
 }
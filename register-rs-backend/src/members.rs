@@ -0,0 +1,54 @@
+use crate::Result;
+use chrono::{Local, NaiveDate};
+use getset::{Getters, WithSetters};
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Getters, WithSetters, Serialize, Deserialize)]
+#[getset(get = "pub", set_with = "pub")]
+pub struct Member {
+    number: String,
+    name: String,
+    valid_until: NaiveDate,
+}
+
+impl Member {
+    pub fn new(number: String, name: String, valid_until: NaiveDate) -> Self {
+        Self {
+            number,
+            name,
+            valid_until,
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.valid_until >= Local::now().date_naive()
+    }
+
+    fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            number: row.get("number")?,
+            name: row.get("name")?,
+            valid_until: row.get("valid_until")?,
+        })
+    }
+
+    pub async fn get_all() -> Result<Vec<Self>> {
+        remote!("/members");
+        select!(
+            "SELECT number, name, valid_until FROM members ORDER BY name",
+            Self::from_row
+        )
+    }
+
+    //Renewing a membership is done by inserting it again with a new date
+    pub async fn insert_or_update(self) -> Result<()> {
+        remote!("/members/save", self);
+        insert!(
+            "INSERT INTO members (number, name, valid_until) VALUES (?1, ?2, ?3)
+                ON CONFLICT(number) DO UPDATE SET name=?2, valid_until=?3",
+            params![self.number, self.name, self.valid_until]
+        )?;
+        Ok(())
+    }
+}
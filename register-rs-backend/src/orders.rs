@@ -0,0 +1,169 @@
+use crate::{Result, settings::Settings};
+use chrono::{DateTime, Local};
+use getset::Getters;
+use indexmap::IndexMap;
+use rusqlite::{
+    Connection, params,
+    types::{FromSql, FromSqlError, ToSql, ToSqlOutput, ValueRef},
+};
+use serde::{Deserialize, Serialize};
+use strum::Display;
+
+pub(crate) mod print;
+
+//Set from the kitchen display
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Display, Serialize, Deserialize)]
+pub enum OrderStatus {
+    #[default]
+    #[strum(to_string = "Ny")]
+    Open,
+    #[strum(to_string = "Tillagas")]
+    InProgress,
+    #[strum(to_string = "Klar")]
+    Done,
+}
+
+impl FromSql for OrderStatus {
+    fn column_result(value: ValueRef<'_>) -> rusqlite::types::FromSqlResult<Self> {
+        match value {
+            ValueRef::Text(b"open") => Ok(Self::Open),
+            ValueRef::Text(b"in_progress") => Ok(Self::InProgress),
+            ValueRef::Text(b"done") => Ok(Self::Done),
+            _ => Err(FromSqlError::InvalidType),
+        }
+    }
+}
+
+impl ToSql for OrderStatus {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::Borrowed(ValueRef::Text(match self {
+            Self::Open => b"open",
+            Self::InProgress => b"in_progress",
+            Self::Done => b"done",
+        })))
+    }
+}
+
+//Food lines of a receipt sent to the kitchen, numbered from 1 each day so it can be called out
+#[derive(Debug, Default, Clone, PartialEq, Eq, Getters, Serialize, Deserialize)]
+#[getset(get = "pub")]
+pub struct Order {
+    number: i64,
+    receipt: DateTime<Local>,
+    //Item -> Amount
+    items: Vec<(String, i32)>,
+    status: OrderStatus,
+}
+
+type OrderRow = (DateTime<Local>, i64, OrderStatus, String, i32);
+
+fn order_row(row: &rusqlite::Row) -> rusqlite::Result<OrderRow> {
+    Ok((
+        row.get("receipt")?,
+        row.get("number")?,
+        row.get("status")?,
+        row.get("item")?,
+        row.get("amount")?,
+    ))
+}
+
+//One row per food line, grouped into orders in the order they come
+fn group(mut rows: impl Iterator<Item = Result<OrderRow>>) -> Result<Vec<Order>> {
+    Ok(rows
+        .try_fold(IndexMap::new(), |mut orders, row| {
+            let (receipt, number, status, item, amount) = row?;
+            orders
+                .entry(receipt)
+                .or_insert_with(|| Order {
+                    number,
+                    receipt,
+                    items: Vec::new(),
+                    status,
+                })
+                .items
+                .push((item, amount));
+            Ok::<_, crate::Error>(orders)
+        })?
+        .into_values()
+        .collect())
+}
+
+impl Order {
+    pub(crate) fn new(receipt: DateTime<Local>, items: Vec<(String, i32)>) -> Self {
+        Self {
+            receipt,
+            items,
+            ..Default::default()
+        }
+    }
+
+    //Orders from the last day, oldest first
+    pub async fn get_recents() -> Result<Vec<Self>> {
+        remote!("/orders");
+        group(select!(
+            "SELECT orders.number, orders.receipt, orders.status, receipt_item.item,
+                    receipt_item.amount
+                FROM orders
+                    INNER JOIN receipt_item ON orders.receipt = receipt_item.receipt
+                    INNER JOIN menu ON receipt_item.item = menu.name
+                WHERE menu.category = 'food' AND orders.receipt > date('now','-1 day')
+                ORDER BY orders.receipt",
+            order_row,
+            ..
+        ))
+    }
+
+    //Orders the kitchen has not finished, oldest first so they are made in order
+    pub async fn get_open() -> Result<Vec<Self>> {
+        remote!("/orders/open");
+        group(select!(
+            "SELECT orders.number, orders.receipt, orders.status, receipt_item.item,
+                    receipt_item.amount
+                FROM orders
+                    INNER JOIN receipt_item ON orders.receipt = receipt_item.receipt
+                    INNER JOIN menu ON receipt_item.item = menu.name
+                WHERE menu.category = 'food' AND orders.status != 'done'
+                    AND orders.receipt > date('now','-1 day')
+                ORDER BY orders.receipt",
+            order_row,
+            ..
+        ))
+    }
+
+    pub async fn set_status(self, status: OrderStatus) -> Result<()> {
+        remote!("/orders/status", (self, status));
+        insert!(
+            "UPDATE orders SET status=?2 WHERE receipt=?1",
+            params![self.receipt, status]
+        )?;
+        Ok(())
+    }
+
+    //The number is taken in the same statement as the order is stored, like receipt numbers
+    pub(crate) fn insert(mut self, conn: &Connection) -> Result<Self> {
+        conn.prepare_cached(
+            "INSERT INTO orders (number, receipt)
+                VALUES ((SELECT IFNULL(MAX(number), 0) + 1 FROM orders
+                    WHERE date(receipt, 'localtime') = date(?1, 'localtime')), ?1)",
+        )?
+        .execute(params![self.receipt])?;
+
+        self.number = conn
+            .prepare_cached("SELECT number FROM orders WHERE receipt=?1")?
+            .query_row(params![self.receipt], |row| row.get("number"))?;
+
+        Ok(self)
+    }
+
+    //Sold on another register, kept with the number it was called out with there. Done since it
+    //was made by the kitchen at that register
+    pub(crate) fn upload(conn: &Connection, receipt: DateTime<Local>, number: i64) -> Result<()> {
+        conn.prepare_cached("INSERT INTO orders (number, receipt, status) VALUES (?1, ?2, ?3)")?
+            .execute(params![number, receipt, OrderStatus::Done])?;
+        Ok(())
+    }
+
+    pub async fn print(&self) -> Result<()> {
+        Ok(print::print(self, &Settings::get().await?).await?)
+    }
+}
@@ -0,0 +1,66 @@
+use {
+    super::Order,
+    crate::{
+        print_queue,
+        receipts::{
+            escpos,
+            print::{Error, RECEIPT_PATH, Result, blank_document, rule},
+        },
+        settings::Settings,
+    },
+    genpdf::{
+        Alignment, Element,
+        elements::{Break, Paragraph},
+        style::Style,
+    },
+    std::{path::PathBuf, sync::Arc},
+};
+
+//Kitchen ticket without logo or prices, large enough to read from a distance
+fn create_pdf(path: impl Into<PathBuf>, order: &Order, settings: &Settings) -> Result<PathBuf> {
+    let mut doc = blank_document(settings);
+
+    doc.push(
+        Paragraph::new(format!("Order {}", order.number))
+            .aligned(Alignment::Center)
+            .styled(Style::new().bold().with_font_size(24)),
+    );
+    doc.push(Paragraph::new(order.receipt.format("%T").to_string()).aligned(Alignment::Center));
+    doc.push(rule(settings));
+    for (item, amount) in order.items.iter() {
+        doc.push(
+            Paragraph::new(format!("{amount} x {item}"))
+                .styled(Style::new().bold().with_font_size(14)),
+        );
+    }
+    doc.push(Break::new(1));
+
+    let mut path = path.into();
+    path.push(format!(
+        "order_{}_{}.pdf",
+        order.receipt.format("%F"),
+        order.number
+    ));
+    doc.render_to_file(path.clone())
+        .map_err(|e| Error::Pdf(Arc::new(e)))?;
+
+    Ok(path)
+}
+
+//Kitchen printer first, then the receipt printer and last the PDF
+pub async fn print(order: &Order, settings: &Settings) -> Result<()> {
+    let printer = settings
+        .kitchen_printer()
+        .as_ref()
+        .or(settings.receipt_printer().as_ref());
+    let fallback = match printer {
+        Some(printer) => match escpos::print_order(order, settings, printer).await {
+            Ok(()) => return Ok(()),
+            Err(e) => Some(format!("{e:?}")),
+        },
+        None => None,
+    };
+    let path = RECEIPT_PATH.get().ok_or(Error::NoPrintPath)?;
+    print_queue::push(settings, create_pdf(path, order, settings)?, fallback);
+    Ok(())
+}
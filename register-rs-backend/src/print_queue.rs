@@ -0,0 +1,212 @@
+use {
+    crate::{
+        receipts::print::{Error, Result},
+        settings::Settings,
+    },
+    chrono::{DateTime, Local},
+    getset::Getters,
+    std::{
+        path::PathBuf,
+        sync::{
+            LazyLock, Mutex,
+            atomic::{AtomicU64, Ordering},
+        },
+        time::Duration,
+    },
+    tokio::process::Command,
+};
+
+//Tries per job before it is marked as failed
+const ATTEMPTS: u32 = 3;
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+
+//Jobs are removed when printed unless the ESC/POS printer failed, the PDF is kept either way
+static JOBS: LazyLock<Mutex<Vec<Job>>> = LazyLock::new(Default::default);
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JobState {
+    Pending,
+    Printing,
+    //Printed as a PDF instead of on the ESC/POS printer, kept until removed so it is noticed
+    Printed,
+    //Message from the printer command or why it could not be run
+    Failed(String),
+}
+
+#[derive(Debug, Clone, Getters)]
+#[getset(get = "pub")]
+pub struct Job {
+    id: u64,
+    file: PathBuf,
+    created: DateTime<Local>,
+    attempts: u32,
+    state: JobState,
+    //Why the ESC/POS printer was not used
+    fallback: Option<String>,
+}
+
+//Pending and failed jobs, oldest first
+pub fn jobs() -> Vec<Job> {
+    lock().clone()
+}
+
+//Returns at once, the job is printed in the background
+pub(crate) fn push(settings: &Settings, file: PathBuf, fallback: Option<String>) -> u64 {
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    lock().push(Job {
+        id,
+        file,
+        created: Local::now(),
+        attempts: 0,
+        state: JobState::Pending,
+        fallback,
+    });
+    tokio::spawn(run(id, settings.clone()));
+    id
+}
+
+//Uses the current settings so a fixed printer command is picked up
+pub async fn retry(id: u64) -> crate::Result<()> {
+    let settings = Settings::get().await?;
+    let queued = update(id, |job| {
+        //Pending jobs are already being retried
+        if !matches!(job.state, JobState::Failed(_)) {
+            return false;
+        }
+        job.attempts = 0;
+        job.state = JobState::Pending;
+        true
+    });
+    if queued == Some(true) {
+        tokio::spawn(run(id, settings));
+    }
+    Ok(())
+}
+
+//Gives up on the job, the PDF is left for printing manually
+pub fn remove(id: u64) {
+    lock().retain(|job| job.id != id);
+}
+
+fn lock() -> std::sync::MutexGuard<'static, Vec<Job>> {
+    JOBS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn update<T>(id: u64, f: impl FnOnce(&mut Job) -> T) -> Option<T> {
+    lock().iter_mut().find(|job| job.id == id).map(f)
+}
+
+async fn run(id: u64, settings: Settings) {
+    for attempt in 1..=ATTEMPTS {
+        let Some(file) = update(id, |job| {
+            job.attempts = attempt;
+            job.state = JobState::Printing;
+            job.file.clone()
+        }) else {
+            //Removed while waiting for a retry
+            return;
+        };
+
+        match send(&settings, file).await {
+            Ok(()) => {
+                let kept = update(id, |job| {
+                    job.state = JobState::Printed;
+                    job.fallback.is_some()
+                });
+                if kept != Some(true) {
+                    remove(id);
+                }
+                return;
+            }
+            Err(message) if attempt == ATTEMPTS => {
+                update(id, |job| job.state = JobState::Failed(message));
+            }
+            Err(_) => {
+                update(id, |job| job.state = JobState::Pending);
+                tokio::time::sleep(RETRY_DELAY).await;
+            }
+        }
+    }
+}
+
+async fn send(settings: &Settings, file: PathBuf) -> std::result::Result<(), String> {
+    let output = command(settings, file)
+        .map_err(|e| format!("{e:?}"))?
+        .output()
+        .await
+        .map_err(|e| e.to_string())?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        Err(match stderr.is_empty() {
+            true => output.status.to_string(),
+            false => stderr,
+        })
+    }
+}
+
+//{file} and {printer} in the command are replaced, the file goes last if it is not in the command
+fn command(settings: &Settings, file: PathBuf) -> Result<Command> {
+    let printer = settings.printer_name().as_deref().filter(|p| !p.is_empty());
+    let Some(template) = settings.printer_command() else {
+        return system_printer(file, printer);
+    };
+    let file = file.to_string_lossy();
+
+    let mut args = split(template)
+        .into_iter()
+        .map(|arg| {
+            arg.replace("{file}", &file)
+                .replace("{printer}", printer.unwrap_or_default())
+        })
+        .filter(|arg| !arg.is_empty());
+    let mut command = Command::new(args.next().ok_or(Error::PrintFailed)?);
+    command.args(args);
+    if !template.contains("{file}") {
+        command.arg(file.as_ref());
+    }
+    Ok(command)
+}
+
+//Split on whitespace outside double quotes, so a program in C:\Program Files can be quoted.
+//Backslashes are kept as they are since they separate folders on Windows
+fn split(template: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut arg: Option<String> = None;
+    let mut quoted = false;
+    for c in template.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                arg.get_or_insert_with(String::new);
+            }
+            c if c.is_whitespace() && !quoted => args.extend(arg.take()),
+            c => arg.get_or_insert_with(String::new).push(c),
+        }
+    }
+    args.extend(arg);
+    args
+}
+
+//PDFtoPrinter uses the default printer when no name is given
+#[cfg(target_os = "windows")]
+fn system_printer(file: PathBuf, printer: Option<&str>) -> Result<Command> {
+    let mut pdf_to_printer = dirs::config_dir().ok_or(Error::NoConfPath)?;
+    pdf_to_printer.push("smaland_register");
+    pdf_to_printer.push("PDFtoPrinter.exe");
+    let mut command = Command::new(pdf_to_printer);
+    command.arg(file).args(printer);
+    Ok(command)
+}
+
+#[cfg(not(target_os = "windows"))]
+fn system_printer(file: PathBuf, printer: Option<&str>) -> Result<Command> {
+    let mut command = Command::new("/usr/bin/lp");
+    if let Some(printer) = printer {
+        command.arg("-d").arg(printer);
+    }
+    command.arg(file);
+    Ok(command)
+}
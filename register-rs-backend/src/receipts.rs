@@ -1,22 +1,62 @@
-use crate::{Result, items::Item};
+use crate::{
+    CONNECTION, Result,
+    accounts::{Account, TOP_UP},
+    items::{Category, Item},
+    orders::{self, Order},
+    settings::Settings,
+    sync,
+    tickets::Ticket,
+    vouchers::Voucher,
+};
 use chrono::{DateTime, Local};
 use getset::WithSetters;
 use indexmap::IndexMap;
 use rusqlite::{Row, params};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, sync::OnceLock};
 
+pub(crate) mod escpos;
 pub mod payments;
 pub(crate) mod print;
 
 pub use payments::Payment;
 
-#[derive(Debug, Default, Clone, WithSetters)]
+pub(crate) static REGISTER: OnceLock<String> = OnceLock::new();
+
+#[derive(Debug, Default, Clone, WithSetters, Serialize, Deserialize)]
 #[getset(set_with = "pub")]
 pub struct Receipt {
     //Item -> Amount
     #[getset(skip)]
+    #[serde(with = "indexmap::map::serde_seq")]
     items: IndexMap<Item, i32>,
     time: DateTime<Local>,
     payment: Payment,
+    //Bartender confirmed that ID was checked
+    age_verified: bool,
+    //Member number, items inserted after it is set get member prices
+    member: Option<String>,
+    //Prepaid card that is topped up or paid with
+    account: Option<String>,
+    //Gift voucher code when paid with one
+    voucher: Option<String>,
+    #[getset(skip)]
+    tickets: Vec<Ticket>,
+    //Sequential number, allocated when the sale is stored
+    #[getset(skip)]
+    number: Option<i64>,
+    //Kitchen order number when food was sold, called out when it is ready
+    #[getset(skip)]
+    order: Option<i64>,
+    //Why the kitchen ticket could not be printed, the sale is stored anyway
+    #[getset(skip)]
+    kitchen_error: Option<String>,
+    //Register the sale was made on, see set_register
+    #[getset(skip)]
+    register: Option<String>,
+    //Printed again from the transactions tab
+    #[getset(skip)]
+    copy: bool,
 }
 
 impl Receipt {
@@ -29,6 +69,10 @@ impl Receipt {
     }
 
     pub fn insert(&mut self, item: Item, amount: i32) {
+        let item = match (&self.member, *item.member_price()) {
+            (Some(_), Some(price)) => item.with_price(price),
+            _ => item,
+        };
         *self.items.entry(item).or_insert(0) += amount;
     }
 
@@ -36,6 +80,86 @@ impl Receipt {
         self.items.is_empty()
     }
 
+    pub fn is_age_verified(&self) -> bool {
+        self.age_verified
+    }
+
+    pub fn member(&self) -> Option<&str> {
+        self.member.as_deref()
+    }
+
+    pub fn account(&self) -> Option<&str> {
+        self.account.as_deref()
+    }
+
+    pub fn voucher(&self) -> Option<&str> {
+        self.voucher.as_deref()
+    }
+
+    //Message on Swish payments so they can be matched to the receipt
+    pub fn reference(&self) -> String {
+        format!("Kvitto {}", self.time.format("%Y%m%d-%H%M%S"))
+    }
+
+    pub fn number(&self) -> Option<i64> {
+        self.number
+    }
+
+    pub fn order(&self) -> Option<i64> {
+        self.order
+    }
+
+    pub fn kitchen_error(&self) -> Option<&str> {
+        self.kitchen_error.as_deref()
+    }
+
+    pub fn register(&self) -> Option<&str> {
+        self.register.as_deref()
+    }
+
+    pub fn as_copy(self) -> Self {
+        Self { copy: true, ..self }
+    }
+
+    pub fn tickets(&self) -> &[Ticket] {
+        &self.tickets
+    }
+
+    //One ticket per ticket item sold, made before printing so the numbers end up on the receipt
+    pub fn issue_tickets(mut self) -> Self {
+        self.tickets = self
+            .items
+            .iter()
+            .filter(|(item, _)| *item.category() == Category::Ticket)
+            .flat_map(|(item, amount)| (0..*amount).map(|_| item.name().clone()))
+            .map(|item| Ticket::new(item, self.time))
+            .collect();
+        self
+    }
+
+    //Amount put on the prepaid card
+    pub fn top_up(&self) -> i32 {
+        self.items
+            .iter()
+            .filter(|(item, _)| item.is_special() && item.name() == TOP_UP)
+            .map(|(item, amount)| item.price() * amount)
+            .sum()
+    }
+
+    //VAT in öre per rate, prices include VAT and top-ups are taxed when the balance is used
+    pub fn vat(&self) -> BTreeMap<i32, i32> {
+        self.items
+            .iter()
+            .filter(|(item, _)| !(item.is_special() && item.name() == TOP_UP))
+            .fold(BTreeMap::new(), |mut rates, (item, amount)| {
+                *rates.entry(item.category().vat()).or_insert(0) += item.price() * amount;
+                rates
+            })
+            .into_iter()
+            .map(|(rate, gross)| (rate, (gross * 100 * rate + (100 + rate) / 2) / (100 + rate)))
+            .collect()
+    }
+
     pub fn sum(&self) -> i32 {
         self.items
             .iter()
@@ -48,52 +172,233 @@ impl Receipt {
     }
 
     pub async fn get_recents() -> Result<IndexMap<DateTime<Local>, Self>> {
-        select!(
+        remote!("/receipts");
+        let mut receipts = select!(
             "SELECT * FROM receipts_view \
                 WHERE time > date('now','-1 day') ORDER BY time DESC",
             RawEntry::from_row,
             ..
         )
-        .fold(Ok(IndexMap::new()), |res, raw| {
+        .fold(Ok::<_, crate::Error>(IndexMap::new()), |res, raw| {
             let RawEntry {
                 time,
                 amount,
                 item,
                 payment,
+                member,
+                number,
+                register,
             } = raw?;
             res.map(|mut hm| {
-                let r = hm.entry(time).or_insert_with(|| Self::new(time, payment));
+                let r = hm.entry(time).or_insert_with(|| Self {
+                    number,
+                    register,
+                    ..Self::new(time, payment).with_member(member)
+                });
                 r.insert(item, amount);
                 hm
             })
-        })
+        })?;
+
+        //Needed to reprint the QR codes
+        for ticket in Ticket::get_recents().await? {
+            if let Some(receipt) = receipts.get_mut(ticket.sold()) {
+                receipt.tickets.push(ticket);
+            }
+        }
+
+        for order in Order::get_recents().await? {
+            if let Some(receipt) = receipts.get_mut(order.receipt()) {
+                receipt.order = Some(*order.number());
+            }
+        }
+
+        Ok(receipts)
     }
 
     pub async fn print(&self) -> Result<()> {
-        Ok(print::print(self).await?)
+        Ok(print::print(self, &Settings::get().await?).await?)
+    }
+
+    //Only on cash sales, the drawer is connected to the ESC/POS receipt printer
+    pub async fn open_drawer(&self) -> Result<()> {
+        let settings = Settings::get().await?;
+        match settings.receipt_printer() {
+            Some(printer) if *settings.cash_drawer() && self.payment == Payment::Cash => {
+                Ok(escpos::open_drawer(&settings, printer).await?)
+            }
+            _ => Ok(()),
+        }
     }
 
-    pub async fn insert_sale(mut self) -> Result<()> {
-        //FIXME start transaction? otherwise could get incomplete receipts
-        insert!(
-            "INSERT INTO receipts (time, method) VALUES (?1, ?2)",
-            params![self.time, self.payment]
-        )?;
+    //Returns the receipt with its number so it can be printed
+    pub async fn insert_sale(mut self) -> Result<Self> {
+        //Tagged before it is sent so the server stores the register it came from
+        if self.register.is_none() {
+            self.register = REGISTER.get().cloned();
+        }
+        remote!("/receipts/sale", self);
+
+        //All or nothing, the payment, receipt, number, tickets, order and outbox entry are stored
+        //together so a failure leaves neither money moved nor a gap in the numbers
+        let order = {
+            let mut conn = CONNECTION
+                .get()
+                .ok_or(crate::Error::NotConnected)?
+                .lock()
+                .await;
+            let tx = conn.transaction()?;
+            let order = self.store(&tx)?;
+            tx.commit()?;
+            order
+        };
+
+        //Printed once the sale is stored so a printer problem does not make it look unsold
+        if let Some(order) = order {
+            self.kitchen_error = print_order(&order).await.err().map(|e| format!("{e:?}"));
+        }
+        Ok(self)
+    }
 
-        //FIXME (not tested) this might inf-lock the db since we dont drop the handle from above
-        for (item, amount) in self.items.drain(..) {
-            item.insert_sale(self.time, amount).await?;
+    //Everything insert_sale writes, in the transaction it is given
+    fn store(&mut self, conn: &rusqlite::Connection) -> Result<Option<Order>> {
+        //Balances are only kept in this database when syncing, so the same money could be spent
+        //again on every other register
+        if sync::is_enabled()
+            && (matches!(self.payment, Payment::Account | Payment::Voucher) || self.top_up() != 0)
+        {
+            return Err(crate::Error::NotWhileSyncing);
+        }
+        //Debit first so nothing is stored if the balance is too low
+        if self.payment == Payment::Account {
+            let card = self.account.as_deref().ok_or(crate::Error::NoAccount)?;
+            Account::debit(conn, card, self.time, self.sum())?;
+        }
+        if self.payment == Payment::Voucher {
+            let code = self.voucher.as_deref().ok_or(crate::Error::NoVoucher)?;
+            Voucher::redeem(conn, code, self.time, self.sum())?;
+        }
+        let top_up = self.top_up();
+        if top_up != 0 {
+            let card = self.account.as_deref().ok_or(crate::Error::NoAccount)?;
+            Account::credit(conn, card, self.time, top_up)?;
         }
 
+        //Every register has its own series, the number is taken in the same statement as the receipt
+        //is stored so there are no gaps and no two receipts get the same number
+        conn.prepare_cached(
+            "INSERT INTO receipts (time, method, age_verified, event, member, account, voucher,
+                    number, register)
+                VALUES (?1, ?2, ?3, (SELECT id FROM events WHERE active), ?4, ?5, ?6,
+                    (SELECT IFNULL(MAX(number), 0) + 1 FROM receipts WHERE register IS ?7), ?7)",
+        )?
+        .execute(params![
+            self.time,
+            self.payment,
+            self.age_verified,
+            self.member,
+            self.account,
+            self.voucher,
+            self.register
+        ])?;
+
+        self.number = Some(
+            conn.prepare_cached("SELECT number FROM receipts WHERE time=?1")?
+                .query_row(params![self.time], |row| row.get("number"))?,
+        );
+
+        for (item, amount) in self.items.iter() {
+            item.insert_sale(conn, self.time, *amount)?;
+        }
+
+        for ticket in self.tickets.iter() {
+            ticket.insert(conn)?;
+        }
+
+        let food: Vec<_> = self
+            .items
+            .iter()
+            .filter(|(item, _)| *item.category() == Category::Food)
+            .map(|(item, amount)| (item.name().clone(), *amount))
+            .collect();
+        let order = match food.is_empty() {
+            true => None,
+            false => Some(Order::new(self.time, food).insert(conn)?),
+        };
+        self.order = order.as_ref().map(|order| *order.number());
+
+        sync::receipt_sold(conn, self)?;
+        Ok(order)
+    }
+
+    //Sale from another register, it is only stored once however many times it is sent. The event
+    //is found by the time since it may be sent long after the sale
+    pub(crate) async fn upload(self) -> Result<()> {
+        let mut conn = CONNECTION
+            .get()
+            .ok_or(crate::Error::NotConnected)?
+            .lock()
+            .await;
+        let tx = conn.transaction()?;
+
+        //Sent again when the answer to the last try was lost
+        if tx
+            .prepare_cached("SELECT 1 FROM receipts WHERE time=?1 AND register IS ?2")?
+            .exists(params![self.time, self.register])?
+        {
+            return Ok(());
+        }
+
+        //Keeps the number it was printed with, a receipt from another register at the very same
+        //time is refused instead of being mixed up with this one
+        tx.prepare_cached(
+            "INSERT INTO receipts (time, method, age_verified, event, member, account, voucher,
+                    number, register)
+                VALUES (?1, ?2, ?3,
+                    (SELECT id FROM events WHERE ?1 BETWEEN start_time AND end_time LIMIT 1),
+                    ?4, ?5, ?6, ?7, ?8)",
+        )?
+        .execute(params![
+            self.time,
+            self.payment,
+            self.age_verified,
+            self.member,
+            self.account,
+            self.voucher,
+            self.number,
+            self.register
+        ])?;
+        for (item, amount) in self.items.iter() {
+            item.insert_sale(&tx, self.time, *amount)?;
+        }
+        //Scanned at the door on any register once it has synced
+        for ticket in self.tickets.iter() {
+            ticket.upload(&tx)?;
+        }
+        if let Some(order) = self.order {
+            Order::upload(&tx, self.time, order)?;
+        }
+        tx.commit()?;
         Ok(())
     }
 }
 
+async fn print_order(order: &Order) -> Result<()> {
+    let settings = Settings::get().await?;
+    if *settings.kitchen_tickets() {
+        orders::print::print(order, &settings).await?;
+    }
+    Ok(())
+}
+
 pub(crate) struct RawEntry {
     pub(crate) time: DateTime<Local>,
     pub(crate) amount: i32,
     pub(crate) item: Item,
     pub(crate) payment: Payment,
+    pub(crate) member: Option<String>,
+    pub(crate) number: Option<i64>,
+    pub(crate) register: Option<String>,
 }
 
 impl RawEntry {
@@ -106,6 +411,9 @@ impl RawEntry {
                 .get("payment")
                 .or_else(|_| row.get("method"))
                 .unwrap_or_default(),
+            member: row.get("member").unwrap_or_default(),
+            number: row.get("number").unwrap_or_default(),
+            register: row.get("register").unwrap_or_default(),
         })
     }
 }
@@ -0,0 +1,364 @@
+use {
+    super::{
+        Receipt,
+        print::{Error, Result, kronor, logo},
+    },
+    crate::{orders::Order, settings::Settings},
+    image::{RgbaImage, imageops::FilterType},
+    std::{
+        fs::OpenOptions,
+        io::Write,
+        net::{TcpStream, ToSocketAddrs},
+        sync::Arc,
+        time::Duration,
+    },
+};
+
+const ESC: u8 = 0x1B;
+const GS: u8 = 0x1D;
+const TIMEOUT: Duration = Duration::from_secs(5);
+//203 dpi printers
+const DOTS_PER_MM: u32 = 8;
+
+#[derive(Clone, Copy)]
+enum Align {
+    Left = 0,
+    Center = 1,
+}
+
+//Commands for thermal receipt printers, written to the printer as is
+struct EscPos {
+    buf: Vec<u8>,
+    //Characters per line in the normal font, 48 on 72mm
+    width: usize,
+}
+
+impl EscPos {
+    fn new(settings: &Settings) -> Self {
+        let mut printer = Self {
+            buf: Vec::new(),
+            width: *settings.paper_width() as usize * 2 / 3,
+        };
+        //Reset, then the nordic code page for åäö
+        printer.command(&[ESC, b'@', ESC, b't', 5]);
+        printer
+    }
+
+    fn command(&mut self, bytes: &[u8]) -> &mut Self {
+        self.buf.extend_from_slice(bytes);
+        self
+    }
+
+    fn align(&mut self, align: Align) -> &mut Self {
+        self.command(&[ESC, b'a', align as u8])
+    }
+
+    fn bold(&mut self, bold: bool) -> &mut Self {
+        self.command(&[ESC, b'E', bold as u8])
+    }
+
+    //Double width and height
+    fn large(&mut self, large: bool) -> &mut Self {
+        self.command(&[GS, b'!', if large { 0x11 } else { 0 }])
+    }
+
+    fn line(&mut self, text: &str) -> &mut Self {
+        self.buf.extend(encode(text));
+        self.command(b"\n")
+    }
+
+    //Left and right aligned on the same line
+    fn columns(&mut self, left: &str, right: &str) -> &mut Self {
+        let space = self
+            .width
+            .saturating_sub(left.chars().count() + right.chars().count())
+            .max(1);
+        self.line(&format!("{left}{}{right}", " ".repeat(space)))
+    }
+
+    fn rule(&mut self) -> &mut Self {
+        self.line(&"-".repeat(self.width))
+    }
+
+    fn feed(&mut self, lines: u8) -> &mut Self {
+        self.command(&[ESC, b'd', lines])
+    }
+
+    //Raster bit image, transparent and light pixels are left blank
+    fn image(&mut self, image: &RgbaImage) -> &mut Self {
+        let (width, height) = image.dimensions();
+        let bytes = width.div_ceil(8);
+        self.command(&[GS, b'v', b'0', 0]);
+        self.command(&(bytes as u16).to_le_bytes());
+        self.command(&(height as u16).to_le_bytes());
+        for y in 0..height {
+            for byte in 0..bytes {
+                let bits = (0..8)
+                    .map(|bit| (byte * 8 + bit, bit))
+                    .filter(|(x, _)| *x < width)
+                    .filter(|(x, _)| {
+                        let [r, g, b, a] = image.get_pixel(*x, y).0;
+                        a >= 128 && (r as u32 * 299 + g as u32 * 587 + b as u32 * 114) < 128_000
+                    })
+                    .fold(0u8, |bits, (_, bit)| bits | (0x80 >> bit));
+                self.buf.push(bits);
+            }
+        }
+        self
+    }
+
+    //Printed by the printer itself, model 2 with medium error correction
+    fn qr(&mut self, data: &str) -> &mut Self {
+        let len = (data.len() + 3) as u16;
+        self.command(&[GS, b'(', b'k', 4, 0, 0x31, 0x41, 0x32, 0])
+            .command(&[GS, b'(', b'k', 3, 0, 0x31, 0x43, 8])
+            .command(&[GS, b'(', b'k', 3, 0, 0x31, 0x45, 0x31])
+            .command(&[GS, b'(', b'k'])
+            .command(&len.to_le_bytes())
+            .command(&[0x31, 0x50, 0x30])
+            .command(data.as_bytes())
+            .command(&[GS, b'(', b'k', 3, 0, 0x31, 0x51, 0x30])
+            .command(b"\n")
+    }
+
+    //Feeds past the knife before cutting
+    fn cut(&mut self) -> &mut Self {
+        self.command(&[GS, b'V', 66, 0])
+    }
+
+    //Pulse on pin 2 where the drawer is usually connected
+    fn kick_drawer(&mut self) -> &mut Self {
+        self.command(&[ESC, b'p', 0, 25, 250])
+    }
+}
+
+//Code page 865
+fn encode(text: &str) -> Vec<u8> {
+    text.chars()
+        .map(|c| match c {
+            ' '..='~' => c as u8,
+            'å' => 0x86,
+            'ä' => 0x84,
+            'ö' => 0x94,
+            'Å' => 0x8F,
+            'Ä' => 0x8E,
+            'Ö' => 0x99,
+            'é' => 0x82,
+            'É' => 0x90,
+            'ü' => 0x81,
+            'Ü' => 0x9A,
+            'æ' => 0x91,
+            'Æ' => 0x92,
+            'ø' => 0x9B,
+            'Ø' => 0x9D,
+            '–' | '—' => b'-',
+            _ => b'?',
+        })
+        .collect()
+}
+
+//Half the paper width at most
+fn logo_image(settings: &Settings) -> Result<RgbaImage> {
+    let bytes = logo(settings).map_err(|e| Error::Io(e.kind()))?;
+    let max = settings.paper_width() * DOTS_PER_MM / 2;
+    Ok(image::load_from_memory(&bytes)
+        .map_err(|e| Error::Image(Arc::new(e)))?
+        .resize(max, max, FilterType::Triangle)
+        .to_rgba8())
+}
+
+//Same content as the PDF
+fn render(receipt: &Receipt, settings: &Settings) -> Result<Vec<u8>> {
+    let mut printer = EscPos::new(settings);
+
+    printer.align(Align::Center).image(&logo_image(settings)?);
+    for line in settings.header() {
+        printer.line(line);
+    }
+    if let Some(org_number) = settings.org_number() {
+        printer.line(&format!("Org.nr {org_number}"));
+    }
+    printer.rule();
+    if receipt.copy {
+        printer
+            .large(true)
+            .bold(true)
+            .line("KOPIA")
+            .large(false)
+            .bold(false);
+    }
+
+    printer.align(Align::Left);
+    if let Some(number) = receipt.number {
+        printer.line(&format!("Kvitto nr {number}"));
+    }
+    if let Some(register) = &receipt.register {
+        printer.line(&format!("Kassa {register}"));
+    }
+    printer
+        .line(&receipt.time.format("%F %T").to_string())
+        .rule();
+    if let Some(order) = receipt.order {
+        printer
+            .align(Align::Center)
+            .large(true)
+            .bold(true)
+            .line(&format!("Order {order}"))
+            .large(false)
+            .bold(false)
+            .align(Align::Left)
+            .rule();
+    }
+
+    for (item, amount) in receipt.items.iter() {
+        let total = format!("{}kr", item.price() * amount);
+        if item.is_special() {
+            printer.columns(item.name(), &total);
+        } else {
+            printer
+                .line(item.name())
+                .columns(&format!("  {}x{}kr", amount, item.price()), &total);
+        }
+    }
+
+    printer
+        .rule()
+        .bold(true)
+        .columns("Total", &format!("{}kr", receipt.sum()))
+        .bold(false);
+    let vat = receipt.vat();
+    if !vat.is_empty() {
+        for (rate, amount) in vat.iter() {
+            printer.columns(&format!("Moms {rate}%"), &kronor(*amount));
+        }
+        printer.columns(
+            "Netto",
+            &kronor(receipt.sum() * 100 - vat.values().sum::<i32>()),
+        );
+    }
+    printer.columns("Betalt via", &receipt.payment.to_string());
+    if let Some(member) = &receipt.member {
+        printer.columns("Medlem", member);
+    }
+    if let Some(account) = &receipt.account {
+        printer.columns("Saldokort", account);
+    }
+    if let Some(voucher) = &receipt.voucher {
+        printer.columns("Presentkort", voucher);
+    }
+    if receipt.copy {
+        printer
+            .feed(1)
+            .align(Align::Center)
+            .large(true)
+            .bold(true)
+            .line("KOPIA")
+            .large(false)
+            .bold(false);
+    }
+
+    for ticket in receipt.tickets.iter() {
+        printer
+            .align(Align::Center)
+            .rule()
+            .line(ticket.item())
+            .qr(ticket.number())
+            .line(ticket.number());
+    }
+
+    printer.feed(1).cut();
+
+    Ok(printer.buf)
+}
+
+//Kitchen ticket, only the food in large text
+fn render_order(order: &Order, settings: &Settings) -> Vec<u8> {
+    let mut printer = EscPos::new(settings);
+
+    printer
+        .align(Align::Center)
+        .large(true)
+        .bold(true)
+        .line(&format!("Order {}", order.number()))
+        .large(false)
+        .bold(false)
+        .line(&order.receipt().format("%T").to_string())
+        .rule()
+        .align(Align::Left)
+        .large(true);
+    for (item, amount) in order.items() {
+        printer.line(&format!("{amount} x {item}"));
+    }
+    printer.large(false).feed(2).cut();
+
+    printer.buf
+}
+
+fn send(printer: &str, data: &[u8]) -> Result<()> {
+    match printer.strip_prefix("tcp://") {
+        Some(address) => {
+            let address = match address.contains(':') {
+                true => address.to_string(),
+                false => format!("{address}:9100"),
+            };
+            let address = address
+                .to_socket_addrs()
+                .map_err(|e| Error::Io(e.kind()))?
+                .next()
+                .ok_or(Error::PrintFailed)?;
+            let mut stream =
+                TcpStream::connect_timeout(&address, TIMEOUT).map_err(|e| Error::Io(e.kind()))?;
+            stream
+                .set_write_timeout(Some(TIMEOUT))
+                .map_err(|e| Error::Io(e.kind()))?;
+            stream.write_all(data).map_err(|e| Error::Io(e.kind()))
+        }
+        //Serial ports and USB printers are written to like files
+        None => OpenOptions::new()
+            .write(true)
+            .open(device(printer))
+            .map_err(|e| Error::Io(e.kind()))?
+            .write_all(data)
+            .map_err(|e| Error::Io(e.kind())),
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn device(printer: &str) -> String {
+    match printer.starts_with("COM") {
+        true => format!(r"\\.\{printer}"),
+        false => printer.to_string(),
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn device(printer: &str) -> String {
+    printer.to_string()
+}
+
+//The writes block for up to the timeout when the printer is gone, so they are kept off the async
+//threads the sales run on
+async fn blocking(f: impl FnOnce() -> Result<()> + Send + 'static) -> Result<()> {
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|_| Error::PrintFailed)?
+}
+
+pub(crate) async fn print_order(order: &Order, settings: &Settings, printer: &str) -> Result<()> {
+    let data = render_order(order, settings);
+    let printer = printer.to_string();
+    blocking(move || send(&printer, &data)).await
+}
+
+//Its own job so the drawer opens whether or not the receipt is printed
+pub(crate) async fn open_drawer(settings: &Settings, printer: &str) -> Result<()> {
+    let mut drawer = EscPos::new(settings);
+    drawer.kick_drawer();
+    let (data, printer) = (drawer.buf, printer.to_string());
+    blocking(move || send(&printer, &data)).await
+}
+
+pub(crate) async fn print(receipt: &Receipt, settings: &Settings, printer: &str) -> Result<()> {
+    let (receipt, settings, printer) = (receipt.clone(), settings.clone(), printer.to_string());
+    blocking(move || send(&printer, &render(&receipt, &settings)?)).await
+}
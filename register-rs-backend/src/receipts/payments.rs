@@ -1,14 +1,24 @@
 use {
     rusqlite::types::{FromSql, FromSqlError, ToSql, ToSqlOutput, ValueRef},
+    serde::{Deserialize, Serialize},
     strum::{Display, VariantArray},
 };
 
-#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash, Display, VariantArray)]
+#[derive(
+    Default, Debug, Clone, Copy, PartialEq, Eq, Hash, Display, VariantArray, Serialize, Deserialize,
+)]
 pub enum Payment {
     Cash,
     #[default]
     Swish,
     Paypal,
+    //Prepaid card
+    #[strum(to_string = "Saldo")]
+    Account,
+    #[strum(to_string = "Presentkort")]
+    Voucher,
+    #[strum(to_string = "Kort")]
+    Card,
 }
 
 impl FromSql for Payment {
@@ -17,6 +27,9 @@ impl FromSql for Payment {
             ValueRef::Text(b"cash") => Ok(Self::Cash),
             ValueRef::Text(b"swish") => Ok(Self::Swish),
             ValueRef::Text(b"paypal") => Ok(Self::Paypal),
+            ValueRef::Text(b"account") => Ok(Self::Account),
+            ValueRef::Text(b"voucher") => Ok(Self::Voucher),
+            ValueRef::Text(b"card") => Ok(Self::Card),
             _ => Err(FromSqlError::InvalidType),
         }
     }
@@ -28,6 +41,19 @@ impl ToSql for Payment {
             Self::Cash => b"cash",
             Self::Swish => b"swish",
             Self::Paypal => b"paypal",
+            Self::Account => b"account",
+            Self::Voucher => b"voucher",
+            Self::Card => b"card",
         })))
     }
 }
+
+//Prefilled Swish payment, "C" followed by payee;amount;message;editable fields
+//where 0 locks all of them so the guest can not change the amount
+pub fn swish_qr(payee: &str, amount: i32, message: &str) -> String {
+    format!(
+        "C{};{amount};{};0",
+        payee.replace([' ', '-'], ""),
+        message.replace(';', ",")
+    )
+}
@@ -1,10 +1,14 @@
 use {
     super::Receipt,
+    crate::{print_queue, settings::Settings, tickets::Ticket},
     genpdf::{
-        Alignment, Document, SimplePageDecorator,
+        Alignment, Document, Element, SimplePageDecorator,
         elements::{Break, Image, Paragraph, TableLayout, Text},
         fonts,
+        style::Style,
     },
+    image::{DynamicImage, Luma},
+    qrcode::{QrCode, types::QrError},
     std::{
         io::Cursor,
         path::PathBuf,
@@ -22,31 +26,80 @@ pub(crate) static FONT: LazyLock<fonts::FontData> = LazyLock::new(|| {
     .unwrap()
 });
 
-fn create_pdf(path: impl Into<PathBuf>, receipt: &Receipt) -> Result<PathBuf> {
+//Logo from the settings, otherwise the built in one
+pub(crate) fn logo(settings: &Settings) -> std::io::Result<Vec<u8>> {
+    match settings.logo() {
+        Some(path) => std::fs::read(path),
+        None => Ok(include_bytes!("../../../resources/logga.png").to_vec()),
+    }
+}
+
+//Dashes across the roll, 24 fit on 72mm
+pub(crate) fn rule(settings: &Settings) -> Paragraph {
+    Paragraph::new("–".repeat(*settings.paper_width() as usize / 3)).aligned(Alignment::Center)
+}
+
+//Empty receipt roll
+pub(crate) fn blank_document(settings: &Settings) -> Document {
     let mut doc = Document::new(fonts::FontFamily {
         regular: FONT.clone(),
         bold: FONT.clone(),
         italic: FONT.clone(),
         bold_italic: FONT.clone(),
     });
-    doc.set_paper_size((72, 300));
+    doc.set_paper_size((*settings.paper_width(), 300));
     doc.set_page_decorator({
         let mut dec = SimplePageDecorator::new();
         dec.set_margins((10, 5));
         dec
     });
+    doc
+}
+
+//Receipt roll with logo and header lines, shared with vouchers
+pub(crate) fn new_document(settings: &Settings) -> Result<Document> {
+    let mut doc = blank_document(settings);
 
-    let logga = Cursor::new(include_bytes!("../../../resources/logga.png"));
+    let logga = Cursor::new(logo(settings).map_err(|e| Error::Io(e.kind()))?);
     doc.push(
         Image::from_reader(logga)
-            .unwrap()
+            .map_err(|e| Error::Pdf(Arc::new(e)))?
             .with_alignment(Alignment::Center),
     );
 
-    doc.push(Paragraph::new("Smålands Nation").aligned(Alignment::Center));
-    doc.push(Paragraph::new("Nyhemsgatan 30").aligned(Alignment::Center));
-    doc.push(Paragraph::new("302 49 Halmstad").aligned(Alignment::Center));
-    doc.push(Paragraph::new("–".repeat(24)).aligned(Alignment::Center));
+    for line in settings.header() {
+        doc.push(Paragraph::new(line.clone()).aligned(Alignment::Center));
+    }
+    if let Some(org_number) = settings.org_number() {
+        doc.push(Paragraph::new(format!("Org.nr {org_number}")).aligned(Alignment::Center));
+    }
+    doc.push(rule(settings));
+
+    Ok(doc)
+}
+
+fn create_pdf(path: impl Into<PathBuf>, receipt: &Receipt, settings: &Settings) -> Result<PathBuf> {
+    let mut doc = new_document(settings)?;
+
+    if receipt.copy {
+        doc.push(copy_mark());
+    }
+    if let Some(number) = receipt.number {
+        doc.push(Text::new(format!("Kvitto nr {number}")));
+    }
+    if let Some(register) = &receipt.register {
+        doc.push(Text::new(format!("Kassa {register}")));
+    }
+    doc.push(Text::new(format!("{}", receipt.time.format("%F %T"))));
+    doc.push(rule(settings));
+    if let Some(order) = receipt.order {
+        doc.push(
+            Paragraph::new(format!("Order {order}"))
+                .aligned(Alignment::Center)
+                .styled(Style::new().bold().with_font_size(18)),
+        );
+        doc.push(rule(settings));
+    }
 
     for (item, amount) in receipt.items.iter() {
         doc.push(Text::new(item.name().clone()));
@@ -70,7 +123,7 @@ fn create_pdf(path: impl Into<PathBuf>, receipt: &Receipt) -> Result<PathBuf> {
         }
     }
 
-    doc.push(Paragraph::new("–".repeat(24)).aligned(Alignment::Center));
+    doc.push(rule(settings));
     doc.push({
         let mut tbl = TableLayout::new(vec![1, 1]);
         tbl.row()
@@ -80,6 +133,29 @@ fn create_pdf(path: impl Into<PathBuf>, receipt: &Receipt) -> Result<PathBuf> {
             .expect("Couldn't Table Total");
         tbl
     });
+    let vat = receipt.vat();
+    if !vat.is_empty() {
+        doc.push({
+            let mut tbl = TableLayout::new(vec![1, 1]);
+            let mut total = 0;
+            for (rate, amount) in vat {
+                total += amount;
+                tbl.row()
+                    .element(Text::new(format!("Moms {rate}%")))
+                    .element(Paragraph::new(kronor(amount)).aligned(Alignment::Right))
+                    .push()
+                    .expect("Couldn't Table VAT");
+            }
+            tbl.row()
+                .element(Text::new("Netto"))
+                .element(
+                    Paragraph::new(kronor(receipt.sum() * 100 - total)).aligned(Alignment::Right),
+                )
+                .push()
+                .expect("Couldn't Table Net");
+            tbl
+        });
+    }
     doc.push({
         let mut tbl = TableLayout::new(vec![1, 1]);
         tbl.row()
@@ -89,52 +165,100 @@ fn create_pdf(path: impl Into<PathBuf>, receipt: &Receipt) -> Result<PathBuf> {
             .expect("Couldn't Table Payment");
         tbl
     });
+    if let Some(member) = &receipt.member {
+        doc.push({
+            let mut tbl = TableLayout::new(vec![1, 1]);
+            tbl.row()
+                .element(Text::new("Medlem"))
+                .element(Paragraph::new(member.clone()).aligned(Alignment::Right))
+                .push()
+                .expect("Couldn't Table Member");
+            tbl
+        });
+    }
+    if let Some(account) = &receipt.account {
+        doc.push({
+            let mut tbl = TableLayout::new(vec![1, 1]);
+            tbl.row()
+                .element(Text::new("Saldokort"))
+                .element(Paragraph::new(account.clone()).aligned(Alignment::Right))
+                .push()
+                .expect("Couldn't Table Account");
+            tbl
+        });
+    }
+    if let Some(voucher) = &receipt.voucher {
+        doc.push({
+            let mut tbl = TableLayout::new(vec![1, 1]);
+            tbl.row()
+                .element(Text::new("Presentkort"))
+                .element(Paragraph::new(voucher.clone()).aligned(Alignment::Right))
+                .push()
+                .expect("Couldn't Table Voucher");
+            tbl
+        });
+    }
     doc.push(Break::new(1));
-    doc.push(Text::new(format!("{}", receipt.time.format("%F %T"))));
-    doc.push(Break::new(1));
+    if receipt.copy {
+        doc.push(copy_mark());
+    }
+
+    for ticket in receipt.tickets.iter() {
+        doc.push(rule(settings));
+        doc.push(Paragraph::new(ticket.item().clone()).aligned(Alignment::Center));
+        doc.push(ticket_qr(ticket)?);
+        doc.push(Paragraph::new(ticket.number().clone()).aligned(Alignment::Center));
+        doc.push(Break::new(1));
+    }
 
     let mut path = path.into();
-    path.push(format!("receipt_{}.pdf", receipt.time.format("%F_%T")).replace(':', "-"));
+    path.push(
+        format!(
+            "receipt_{}{}.pdf",
+            receipt.time.format("%F_%T"),
+            if receipt.copy { "_kopia" } else { "" }
+        )
+        .replace(':', "-"),
+    );
     doc.render_to_file(path.clone())
         .map_err(|e| Error::Pdf(Arc::new(e)))?;
 
     Ok(path)
 }
 
-#[cfg(target_os = "windows")]
-pub async fn print(receipt: &Receipt) -> Result<()> {
-    let filename = create_pdf(RECEIPT_PATH.get().ok_or(Error::NoPrintPath)?, &receipt)?;
-    let mut pdf_to_printer = dirs::config_dir().ok_or(Error::NoConfPath)?;
-    pdf_to_printer.push("smaland_register");
-    pdf_to_printer.push("PDFtoPrinter.exe");
-    if std::process::Command::new(pdf_to_printer)
-        .args([filename])
-        .output()
-        .map_err(|e| Error::Io(e.kind()))?
-        .status
-        .success()
-    {
-        Ok(())
-    } else {
-        Err(Error::PrintFailed)?
-    }
+//The sign on its own since -50 öre has no kronor to carry it
+pub(crate) fn kronor(ore: i32) -> String {
+    let sign = if ore < 0 { "-" } else { "" };
+    format!("{sign}{},{:02}kr", (ore / 100).abs(), (ore % 100).abs())
 }
 
-#[cfg(not(target_os = "windows"))]
-pub async fn print(receipt: &Receipt) -> Result<()> {
-    let filename = create_pdf(RECEIPT_PATH.get().ok_or(Error::NoPrintPath)?, receipt)?;
-    println!("{:?}", filename.display());
-    if std::process::Command::new("/usr/bin/lp")
-        .args([filename])
-        .output()
-        .map_err(|e| Error::Io(e.kind()))?
-        .status
-        .success()
-    {
-        Ok(())
-    } else {
-        Err(Error::PrintFailed)
-    }
+//Reprints are marked at the top and bottom so they can not be mistaken for the original
+fn copy_mark() -> impl Element {
+    Paragraph::new("KOPIA")
+        .aligned(Alignment::Center)
+        .styled(Style::new().bold().with_font_size(24))
+}
+
+fn ticket_qr(ticket: &Ticket) -> Result<Image> {
+    let code = QrCode::new(ticket.number().as_bytes()).map_err(Error::Qr)?;
+    let image = code.render::<Luma<u8>>().module_dimensions(10, 10).build();
+    Ok(Image::from_dynamic_image(DynamicImage::ImageLuma8(image))
+        .map_err(|e| Error::Pdf(Arc::new(e)))?
+        .with_alignment(Alignment::Center))
+}
+
+//Straight to the ESC/POS printer when one is set, the PDF is printed if that fails
+pub async fn print(receipt: &Receipt, settings: &Settings) -> Result<()> {
+    let fallback = match settings.receipt_printer() {
+        Some(printer) => match super::escpos::print(receipt, settings, printer).await {
+            Ok(()) => return Ok(()),
+            Err(e) => Some(format!("{e:?}")),
+        },
+        None => None,
+    };
+    let path = RECEIPT_PATH.get().ok_or(Error::NoPrintPath)?;
+    print_queue::push(settings, create_pdf(path, receipt, settings)?, fallback);
+    Ok(())
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -146,4 +270,6 @@ pub enum Error {
     NoConfPath,
     Io(std::io::ErrorKind),
     Pdf(Arc<genpdf::error::Error>),
+    Image(Arc<image::ImageError>),
+    Qr(QrError),
 }
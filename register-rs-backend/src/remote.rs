@@ -0,0 +1,62 @@
+use {
+    crate::{
+        Error, Result,
+        http::{self, Client},
+    },
+    serde::{Serialize, de::DeserializeOwned},
+    std::sync::{Arc, OnceLock},
+};
+
+mod server;
+
+pub use server::serve;
+
+pub(crate) static SERVER: OnceLock<Client> = OnceLock::new();
+
+pub(crate) fn is_connected() -> bool {
+    SERVER.get().is_some()
+}
+
+pub(crate) async fn call<A: Serialize, R: DeserializeOwned>(path: &str, args: &A) -> Result<R> {
+    let client = SERVER.get().ok_or(Error::NotConnected)?;
+    let body = serde_json::to_string(args).map_err(|e| http::Error::Json(Arc::new(e)))?;
+    send(client, path, body).await
+}
+
+//Every call is a POST with the arguments as JSON, the server answers {"Ok": ..} or {"Err": ".."}
+pub(crate) async fn send<R: DeserializeOwned>(
+    client: &Client,
+    path: &str,
+    body: String,
+) -> Result<R> {
+    let response: std::result::Result<R, String> =
+        http::parse(&client.call("POST", path, Some(body)).await?)?;
+    response.map_err(error)
+}
+
+//Errors the GUI acts on are passed back as they were, the rest only as their message
+fn error(message: String) -> Error {
+    match message.as_str() {
+        "NotConnected" => Error::NotConnected,
+        "ItemInUse" => Error::ItemInUse,
+        "SpecialItem" => Error::SpecialItem,
+        "ImageTooLarge" => Error::ImageTooLarge,
+        "InvalidImage" => Error::InvalidImage,
+        "InsufficientFunds" => Error::InsufficientFunds,
+        "InvalidAmount" => Error::InvalidAmount,
+        "NoAccount" => Error::NoAccount,
+        "NoVoucher" => Error::NoVoucher,
+        "VoucherNotValid" => Error::VoucherNotValid,
+        "NotWhileSyncing" => Error::NotWhileSyncing,
+        //Sent as Refused("reason"), the reason is quoted like a JSON string
+        _ => match message
+            .strip_prefix("Refused(")
+            .and_then(|m| m.strip_suffix(')'))
+        {
+            Some(reason) => {
+                Error::Refused(serde_json::from_str(reason).unwrap_or_else(|_| reason.to_string()))
+            }
+            None => Error::Remote(message),
+        },
+    }
+}
@@ -0,0 +1,157 @@
+use {
+    crate::{
+        Result,
+        accounts::Account,
+        events::Event,
+        http,
+        items::{Category, Item, images, transfer::Import},
+        members::Member,
+        orders::{Order, OrderStatus},
+        receipts::Receipt,
+        summary::{Summary, alcohol::AlcoholReport, balances::BalanceStatement},
+        sync,
+        tickets::Ticket,
+        vouchers::Voucher,
+    },
+    chrono::{DateTime, Local},
+    serde::{Serialize, de::DeserializeOwned},
+    std::{future::Future, sync::Arc},
+    tokio::net::{TcpListener, TcpStream},
+};
+
+//Serves the database this process is connected to, one task per request. Only registers sending
+//the key are answered since anything that reaches the address could otherwise sell or top up
+pub async fn serve(address: &str, key: &str) -> std::io::Result<()> {
+    let listener = TcpListener::bind(address).await?;
+    let key: Arc<str> = Arc::from(key);
+    loop {
+        let (stream, _) = listener.accept().await?;
+        tokio::spawn(handle(stream, key.clone()));
+    }
+}
+
+async fn handle(mut stream: TcpStream, key: Arc<str>) {
+    let (status, body) = match http::read_request(&mut stream, &key).await {
+        Ok(request) if request.method == "POST" => match dispatch(&request).await {
+            Some(body) => (200, body),
+            None => (404, String::new()),
+        },
+        Ok(_) => (405, String::new()),
+        Err(http::Error::Unauthorized) => (401, String::new()),
+        Err(http::Error::TooLarge) => (413, String::new()),
+        Err(e) => (400, format!("{e:?}")),
+    };
+    //The register gave up waiting, it sees the error on its side and tries again
+    let _ = http::respond(&mut stream, status, &body).await;
+}
+
+//The paths used by the remote! calls in the rest of the crate and by sync
+async fn dispatch(request: &http::Request) -> Option<String> {
+    let body = request.body.as_str();
+    Some(match request.path.as_str() {
+        "/items" => run(body, |()| Item::get_all()).await,
+        "/items/available" => run(body, |()| Item::get_all_available()).await,
+        "/items/favorites" => run(body, |register: String| Item::get_favorites_at(register)).await,
+        "/items/pinned" => run(body, |register: String| Item::get_pinned_at(register)).await,
+        "/items/favorite" => {
+            run(body, |(item, register, favorite): (Item, String, bool)| {
+                item.change_favorite_at(register, favorite)
+            })
+            .await
+        }
+        "/items/new" => run(body, |item: Item| item.insert_new()).await,
+        "/items/update" => run(body, |(old, new): (Item, Item)| old.update(new)).await,
+        "/items/availability" => {
+            run(body, |(item, available): (Item, bool)| {
+                item.change_availability(available)
+            })
+            .await
+        }
+        "/items/archived" => {
+            run(body, |(item, archived): (Item, bool)| {
+                item.change_archived(archived)
+            })
+            .await
+        }
+        "/items/delete" => run(body, |item: Item| item.delete()).await,
+        "/items/image" => run(body, |id: i64| images::get(id)).await,
+        "/items/image/change" => {
+            run(body, |(item, image): (Item, Option<Vec<u8>>)| {
+                item.change_image(image)
+            })
+            .await
+        }
+        "/items/import" => run(body, |import: Import| import.apply()).await,
+        "/categories/age_checked" => run(body, |()| Category::get_age_checked()).await,
+        "/categories/age_check" => {
+            run(body, |(category, age_check): (Category, bool)| {
+                category.change_age_check(age_check)
+            })
+            .await
+        }
+        "/receipts" => run(body, |()| Receipt::get_recents()).await,
+        "/receipts/sale" => run(body, |receipt: Receipt| receipt.insert_sale()).await,
+        "/summary" => {
+            run(body, |(from, to): (DateTime<Local>, DateTime<Local>)| {
+                Summary::get_sales_summary(from, to)
+            })
+            .await
+        }
+        "/summary/event" => run(body, |event: Event| Summary::get_event_summary(event)).await,
+        "/reports/alcohol" => {
+            run(body, |(from, to): (DateTime<Local>, DateTime<Local>)| {
+                AlcoholReport::get(from, to)
+            })
+            .await
+        }
+        "/reports/balances" => {
+            run(body, |(from, to): (DateTime<Local>, DateTime<Local>)| {
+                BalanceStatement::get(from, to)
+            })
+            .await
+        }
+        "/events" => run(body, |()| Event::get_all()).await,
+        "/events/new" => run(body, |event: Event| event.insert_new()).await,
+        "/events/active" => {
+            run(body, |(event, active): (Event, bool)| {
+                event.change_active(active)
+            })
+            .await
+        }
+        "/members" => run(body, |()| Member::get_all()).await,
+        "/members/save" => run(body, |member: Member| member.insert_or_update()).await,
+        "/accounts" => run(body, |()| Account::get_all()).await,
+        "/accounts/new" => run(body, |account: Account| account.insert_new()).await,
+        "/vouchers" => run(body, |()| Voucher::get_outstanding()).await,
+        "/vouchers/liability" => run(body, |at: DateTime<Local>| Voucher::liability(at)).await,
+        "/vouchers/issue" => run(body, |voucher: Voucher| voucher.issue()).await,
+        "/tickets" => run(body, |()| Ticket::get_recents()).await,
+        "/tickets/scan" => run(body, |number: String| Ticket::scan(number)).await,
+        "/orders" => run(body, |()| Order::get_recents()).await,
+        "/orders/open" => run(body, |()| Order::get_open()).await,
+        "/orders/status" => {
+            run(body, |(order, status): (Order, OrderStatus)| {
+                order.set_status(status)
+            })
+            .await
+        }
+        //Read by sync::receive so a change that can not be read is refused instead of failing
+        "/sync/push" => run(body, |change: serde_json::Value| sync::receive(change)).await,
+        "/sync/menu" => run(body, |()| Item::get_synced()).await,
+        _ => return None,
+    })
+}
+
+//Errors are sent as their debug message, see remote::error
+async fn run<A, R, F>(body: &str, op: impl FnOnce(A) -> F) -> String
+where
+    A: DeserializeOwned,
+    R: Serialize,
+    F: Future<Output = Result<R>>,
+{
+    let result = match serde_json::from_str(body) {
+        Ok(args) => op(args).await.map_err(|e| format!("{e:?}")),
+        Err(e) => Err(format!("{e}")),
+    };
+    serde_json::to_string(&result).unwrap_or_default()
+}
@@ -0,0 +1,164 @@
+use crate::Result;
+use getset::{Getters, WithSetters};
+use rusqlite::params;
+use std::ops::RangeInclusive;
+
+//From 58 mm rolls to 80 mm ones, the receipt layouts break outside it
+pub const PAPER_WIDTHS: RangeInclusive<u32> = 48..=80;
+
+//Stored as key/value rows so new settings do not need a migration
+#[derive(Debug, Clone, PartialEq, Eq, Getters, WithSetters)]
+#[getset(get = "pub", set_with = "pub")]
+pub struct Settings {
+    //Centered at the top of receipts and in the header of reports
+    header: Vec<String>,
+    org_number: Option<String>,
+    //Image file used instead of the built in logo
+    logo: Option<String>,
+    //Width of the receipt roll in mm
+    paper_width: u32,
+    //Reports and menu exports go here instead of the documents folder
+    export_dir: Option<String>,
+    //Run instead of the system printer, {file} and {printer} are replaced and the path of the
+    //PDF is added last if {file} is missing
+    printer_command: Option<String>,
+    //Printer the system printer or {printer} uses, the default printer when not set
+    printer_name: Option<String>,
+    //ESC/POS printer receipts are sent to directly, tcp://address or a serial or USB device
+    receipt_printer: Option<String>,
+    //Print food orders for the kitchen when they are sold
+    kitchen_tickets: bool,
+    //ESC/POS printer in the kitchen, the receipt printer or PDF is used when not set
+    kitchen_printer: Option<String>,
+    //Open the cash drawer connected to the receipt printer on every cash sale
+    cash_drawer: bool,
+    //Second window facing the guests with the receipt and payment
+    customer_display: bool,
+    //Swish Handel number shown as a QR code when paying with Swish
+    swish_number: Option<String>,
+    //Card payments go through the terminal when set, see terminal::provider
+    payment_terminal: Option<String>,
+    //Address of the register server, the menu and receipts are shared through it when set
+    server: Option<String>,
+    //Key the register server and sync server were started with, sent with every request
+    server_key: Option<String>,
+    //Name of this register, stored on its receipts
+    register: Option<String>,
+    //Central register server the local database is synced with when it can be reached
+    sync_server: Option<String>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            header: ["Smålands Nation", "Nyhemsgatan 30", "302 49 Halmstad"]
+                .map(String::from)
+                .to_vec(),
+            org_number: None,
+            logo: None,
+            paper_width: 72,
+            export_dir: None,
+            printer_command: None,
+            printer_name: None,
+            receipt_printer: None,
+            kitchen_tickets: false,
+            kitchen_printer: None,
+            cash_drawer: false,
+            customer_display: false,
+            swish_number: None,
+            payment_terminal: None,
+            server: None,
+            server_key: None,
+            register: None,
+            sync_server: None,
+        }
+    }
+}
+
+impl Settings {
+    pub async fn get() -> Result<Self> {
+        select!(
+            "SELECT key, value FROM settings",
+            |row| Ok((row.get::<_, String>("key")?, row.get::<_, String>("value")?)),
+            ..
+        )
+        .try_fold(Self::default(), |settings, row| {
+            let (key, value) = row?;
+            Ok(match key.as_str() {
+                "header" => settings.with_header(value.lines().map(String::from).collect()),
+                "org_number" => settings.with_org_number(Some(value)),
+                "logo" => settings.with_logo(Some(value)),
+                "paper_width" => match value.parse() {
+                    Ok(width) if PAPER_WIDTHS.contains(&width) => settings.with_paper_width(width),
+                    _ => settings,
+                },
+                "export_dir" => settings.with_export_dir(Some(value)),
+                "printer_command" => settings.with_printer_command(Some(value)),
+                "printer_name" => settings.with_printer_name(Some(value)),
+                "receipt_printer" => settings.with_receipt_printer(Some(value)),
+                "kitchen_tickets" => settings.with_kitchen_tickets(value == "true"),
+                "kitchen_printer" => settings.with_kitchen_printer(Some(value)),
+                "cash_drawer" => settings.with_cash_drawer(value == "true"),
+                "customer_display" => settings.with_customer_display(value == "true"),
+                "swish_number" => settings.with_swish_number(Some(value)),
+                "payment_terminal" => settings.with_payment_terminal(Some(value)),
+                "server" => settings.with_server(Some(value)),
+                "server_key" => settings.with_server_key(Some(value)),
+                "register" => settings.with_register(Some(value)),
+                "sync_server" => settings.with_sync_server(Some(value)),
+                _ => settings,
+            })
+        })
+    }
+
+    //Empty values are removed so the default is used again, except for the header which may be
+    //left empty on purpose
+    pub async fn save(self) -> Result<()> {
+        insert!(
+            "INSERT INTO settings (key, value) VALUES ('header', ?1)
+                ON CONFLICT(key) DO UPDATE SET value=excluded.value",
+            params![self.header.join("\n")]
+        )?;
+        let paper_width = self
+            .paper_width
+            .clamp(*PAPER_WIDTHS.start(), *PAPER_WIDTHS.end());
+        for (key, value) in [
+            ("org_number", self.org_number),
+            ("logo", self.logo),
+            ("paper_width", Some(paper_width.to_string())),
+            ("export_dir", self.export_dir),
+            ("printer_command", self.printer_command),
+            ("printer_name", self.printer_name),
+            ("receipt_printer", self.receipt_printer),
+            (
+                "kitchen_tickets",
+                self.kitchen_tickets.then_some(String::from("true")),
+            ),
+            ("kitchen_printer", self.kitchen_printer),
+            (
+                "cash_drawer",
+                self.cash_drawer.then_some(String::from("true")),
+            ),
+            (
+                "customer_display",
+                self.customer_display.then_some(String::from("true")),
+            ),
+            ("swish_number", self.swish_number),
+            ("payment_terminal", self.payment_terminal),
+            ("server", self.server),
+            ("server_key", self.server_key),
+            ("register", self.register),
+            ("sync_server", self.sync_server),
+        ] {
+            match value.filter(|v| !v.trim().is_empty()) {
+                Some(value) => insert!(
+                    "INSERT INTO settings (key, value) VALUES (?1, ?2)
+                        ON CONFLICT(key) DO UPDATE SET value=excluded.value",
+                    params![key, value]
+                )?,
+                None => insert!("DELETE FROM settings WHERE key=?1", params![key])?,
+            };
+        }
+        Ok(())
+    }
+}
@@ -1,20 +1,33 @@
 use crate::{
     Result,
+    events::Event,
     receipts::{Payment, RawEntry, Receipt},
+    settings::Settings,
+    vouchers::Voucher,
 };
 use chrono::{DateTime, Local};
 use getset::Getters;
 use rusqlite::params;
+use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, path::PathBuf};
 use strum::VariantArray;
 
+pub mod alcohol;
+pub mod balances;
 pub(crate) mod save;
 
-#[derive(Debug, Default, Clone, Getters)]
+pub use alcohol::AlcoholReport;
+pub use balances::BalanceStatement;
+
+#[derive(Debug, Default, Clone, Getters, Serialize, Deserialize)]
 #[getset(get = "pub")]
 pub struct Summary {
     from: DateTime<Local>,
     to: DateTime<Local>,
+    //Only sales during this event
+    event: Option<Event>,
+    //Unused value of vouchers at the end of the period
+    voucher_liability: i32,
     #[getset(skip)]
     data: HashMap<Payment, Receipt>,
 }
@@ -29,36 +42,65 @@ impl Summary {
     }
 
     pub async fn get_sales_summary(from: DateTime<Local>, to: DateTime<Local>) -> Result<Self> {
-        let data = select!(
+        remote!("/summary", (from, to));
+        let data = Self::group(select!(
             "SELECT item, amount, price, special, method FROM receipts_view \
                 WHERE time BETWEEN ?1 AND ?2",
             params![from, to],
             RawEntry::from_row,
             ..
-        )
-        .fold(
-            Result::Ok(HashMap::with_capacity(Payment::VARIANTS.len())),
-            |res, raw| {
+        ))?;
+
+        Ok(Self {
+            from,
+            to,
+            event: None,
+            voucher_liability: Voucher::liability(to).await?,
+            data,
+        })
+    }
+
+    pub async fn get_event_summary(event: Event) -> Result<Self> {
+        remote!("/summary/event", event);
+        let data = Self::group(select!(
+            "SELECT item, amount, price, special, method FROM receipts_view \
+                WHERE event = ?1",
+            params![event.id()],
+            RawEntry::from_row,
+            ..
+        ))?;
+
+        Ok(Self {
+            from: *event.start(),
+            to: *event.end(),
+            voucher_liability: Voucher::liability(*event.end()).await?,
+            event: Some(event),
+            data,
+        })
+    }
+
+    fn group(
+        mut entries: impl Iterator<Item = Result<RawEntry>>,
+    ) -> Result<HashMap<Payment, Receipt>> {
+        entries.try_fold(
+            HashMap::with_capacity(Payment::VARIANTS.len()),
+            |mut hm, raw| {
                 let RawEntry {
                     time,
                     amount,
                     item,
                     payment,
+                    ..
                 } = raw?;
-                res.map(|mut hm| {
-                    let r = hm
-                        .entry(payment)
-                        .or_insert_with(|| Receipt::new(time, payment));
-                    r.insert(item, amount);
-                    hm
-                })
+                hm.entry(payment)
+                    .or_insert_with(|| Receipt::new(time, payment))
+                    .insert(item, amount);
+                Ok(hm)
             },
-        )?;
-
-        Ok(Self { from, to, data })
+        )
     }
 
     pub async fn save(&self) -> Result<PathBuf> {
-        Ok(save::save(self).await?)
+        Ok(save::save(self, &Settings::get().await?).await?)
     }
 }
@@ -0,0 +1,174 @@
+use {
+    super::save::{Error, Result, new_document, period, report_dir},
+    crate::items::{AlcoholKind, Item},
+    crate::settings::Settings,
+    chrono::{DateTime, Local},
+    genpdf::{
+        Alignment, Element,
+        elements::{Break, Paragraph, TableLayout, Text},
+        style::Style,
+    },
+    getset::Getters,
+    rusqlite::params,
+    serde::{Deserialize, Serialize},
+    std::{path::PathBuf, sync::Arc},
+    strum::VariantArray,
+};
+
+//Volumes of alcohol sold, as required by the serving licence
+#[derive(Debug, Default, Clone, Getters, Serialize, Deserialize)]
+#[getset(get = "pub")]
+pub struct AlcoholReport {
+    from: DateTime<Local>,
+    to: DateTime<Local>,
+    //Item -> Amount, only items with alcohol set
+    #[getset(skip)]
+    items: Vec<(Item, i32)>,
+}
+
+impl AlcoholReport {
+    pub async fn get(from: DateTime<Local>, to: DateTime<Local>) -> crate::Result<Self> {
+        remote!("/reports/alcohol", (from, to));
+        let items = select!(
+            "SELECT menu.name, menu.price, menu.alcohol_kind, menu.volume, menu.abv,
+                    SUM(receipts_view.amount) AS amount
+                FROM receipts_view
+                    INNER JOIN menu ON receipts_view.item = menu.name
+                WHERE receipts_view.time BETWEEN ?1 AND ?2
+                    AND menu.alcohol_kind IS NOT NULL
+                GROUP BY menu.name
+                ORDER BY menu.alcohol_kind, menu.name",
+            params![from, to],
+            |row| Ok((Item::from_row(row)?, row.get("amount")?)),
+            _
+        )?;
+
+        Ok(Self { from, to, items })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    //Liters and liters of pure alcohol per kind
+    pub fn totals(&self) -> impl Iterator<Item = (AlcoholKind, f64, f64)> + '_ {
+        AlcoholKind::VARIANTS.iter().map(|kind| {
+            self.items
+                .iter()
+                .filter_map(|(item, amount)| {
+                    Some((item.alcohol().filter(|a| a.kind == *kind)?, amount))
+                })
+                .fold(
+                    (*kind, 0.0, 0.0),
+                    |(kind, liters, pure), (alcohol, amount)| {
+                        (
+                            kind,
+                            liters + alcohol.liters(*amount),
+                            pure + alcohol.pure_liters(*amount),
+                        )
+                    },
+                )
+        })
+    }
+
+    pub async fn save(&self) -> crate::Result<PathBuf> {
+        let settings = Settings::get().await?;
+        Ok(self.create_pdf(report_dir(&settings, self.to)?, &settings)?)
+    }
+
+    fn create_pdf(&self, path: impl Into<PathBuf>, settings: &Settings) -> Result<PathBuf> {
+        let mut doc = new_document(settings)?;
+
+        doc.push(Break::new(1));
+
+        doc.push(
+            Text::new(format!("Alkoholförsäljning {}", period(self.from, self.to)))
+                .styled(Style::new().with_font_size(24)),
+        );
+
+        doc.push(Break::new(2));
+
+        let mut table = TableLayout::new(vec![2, 1, 1, 1, 1, 1]);
+        ["", "Typ", "Volym", "Alkoholhalt", "Antal", "Liter"]
+            .into_iter()
+            .fold(table.row(), |row, e| {
+                row.element(Text::new(e).padded(3).framed())
+            })
+            .push()
+            .expect("Table header failed");
+
+        for (item, amount) in self.items.iter() {
+            let Some(alcohol) = item.alcohol() else {
+                continue;
+            };
+            [
+                item.name().clone(),
+                alcohol.kind.to_string(),
+                format!("{}cl", alcohol.volume),
+                format!("{:.1}%", alcohol.percentage()),
+                format!("{amount}st"),
+                format!("{:.2}l", alcohol.liters(*amount)),
+            ]
+            .into_iter()
+            .fold(table.row(), |row, cell| {
+                row.element(
+                    Paragraph::new(cell)
+                        .aligned(Alignment::Right)
+                        .padded(3)
+                        .framed(),
+                )
+            })
+            .push()
+            .expect("Table row failed");
+        }
+
+        doc.push(table.padded(10));
+
+        doc.push(Break::new(2));
+
+        let mut totals = TableLayout::new(vec![1, 1, 1]);
+        ["Typ", "Liter", "Liter ren alkohol"]
+            .into_iter()
+            .fold(totals.row(), |row, e| {
+                row.element(Text::new(e).padded(3).framed())
+            })
+            .push()
+            .expect("Table header failed");
+
+        for (kind, liters, pure) in self.totals() {
+            [
+                kind.to_string(),
+                format!("{liters:.2}l"),
+                format!("{pure:.2}l"),
+            ]
+            .into_iter()
+            .fold(totals.row(), |row, cell| {
+                row.element(
+                    Paragraph::new(cell)
+                        .aligned(Alignment::Right)
+                        .padded(3)
+                        .framed(),
+                )
+            })
+            .push()
+            .expect("Table row failed");
+        }
+
+        doc.push(totals.padded(10));
+
+        let mut path = path.into();
+        path.push(if self.from == self.to {
+            format!("alkohol_{}.pdf", self.from.format("%F"))
+        } else {
+            format!(
+                "alkohol_{}_{}.pdf",
+                self.from.format("%F"),
+                self.to.format("%F")
+            )
+        });
+        doc.render_to_file(path.clone())
+            .map_err(|e| Error::Pdf(Arc::new(e)))?;
+
+        Ok(path)
+    }
+}
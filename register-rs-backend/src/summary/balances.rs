@@ -0,0 +1,167 @@
+use {
+    super::save::{Error, Result, new_document, period, report_dir},
+    crate::settings::Settings,
+    chrono::{DateTime, Local},
+    genpdf::{
+        Alignment, Element,
+        elements::{Break, Paragraph, TableLayout, Text},
+        style::Style,
+    },
+    getset::Getters,
+    rusqlite::params,
+    serde::{Deserialize, Serialize},
+    std::{path::PathBuf, sync::Arc},
+};
+
+#[derive(Debug, Default, Clone, Getters, Serialize, Deserialize)]
+#[getset(get = "pub")]
+pub struct Balance {
+    card: String,
+    holder: String,
+    opening: i32,
+    credit: i32,
+    debit: i32,
+}
+
+impl Balance {
+    pub fn closing(&self) -> i32 {
+        self.opening + self.credit - self.debit
+    }
+
+    fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            card: row.get("card")?,
+            holder: row.get("holder")?,
+            opening: row.get("opening")?,
+            credit: row.get("credit")?,
+            debit: row.get("debit")?,
+        })
+    }
+}
+
+//Movements on the prepaid cards, the money held is a debt to the card holders
+#[derive(Debug, Default, Clone, Getters, Serialize, Deserialize)]
+#[getset(get = "pub")]
+pub struct BalanceStatement {
+    from: DateTime<Local>,
+    to: DateTime<Local>,
+    balances: Vec<Balance>,
+}
+
+impl BalanceStatement {
+    pub async fn get(from: DateTime<Local>, to: DateTime<Local>) -> crate::Result<Self> {
+        remote!("/reports/balances", (from, to));
+        let balances = select!(
+            "SELECT accounts.card, accounts.holder,
+                    COALESCE(SUM(CASE WHEN account_ledger.time < ?1
+                        THEN account_ledger.amount END), 0) AS opening,
+                    COALESCE(SUM(CASE WHEN account_ledger.time BETWEEN ?1 AND ?2
+                        AND account_ledger.amount > 0 THEN account_ledger.amount END), 0) AS credit,
+                    COALESCE(SUM(CASE WHEN account_ledger.time BETWEEN ?1 AND ?2
+                        AND account_ledger.amount < 0 THEN -account_ledger.amount END), 0) AS debit
+                FROM accounts
+                    LEFT JOIN account_ledger ON account_ledger.card = accounts.card
+                GROUP BY accounts.card
+                ORDER BY accounts.holder",
+            params![from, to],
+            Balance::from_row,
+            _
+        )?;
+
+        Ok(Self { from, to, balances })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.balances.is_empty()
+    }
+
+    pub fn total(&self) -> i32 {
+        self.balances.iter().map(Balance::closing).sum()
+    }
+
+    pub async fn save(&self) -> crate::Result<PathBuf> {
+        let settings = Settings::get().await?;
+        Ok(self.create_pdf(report_dir(&settings, self.to)?, &settings)?)
+    }
+
+    fn create_pdf(&self, path: impl Into<PathBuf>, settings: &Settings) -> Result<PathBuf> {
+        let mut doc = new_document(settings)?;
+
+        doc.push(Break::new(1));
+
+        doc.push(
+            Text::new(format!("Saldokort {}", period(self.from, self.to)))
+                .styled(Style::new().with_font_size(24)),
+        );
+
+        doc.push(Break::new(2));
+
+        let mut table = TableLayout::new(vec![1, 2, 1, 1, 1, 1]);
+        [
+            "Kort",
+            "Innehavare",
+            "Ingående",
+            "Insättningar",
+            "Köp",
+            "Utgående",
+        ]
+        .into_iter()
+        .fold(table.row(), |row, e| {
+            row.element(Text::new(e).padded(3).framed())
+        })
+        .push()
+        .expect("Table header failed");
+
+        for balance in self.balances.iter() {
+            [
+                balance.card.clone(),
+                balance.holder.clone(),
+                format!("{}kr", balance.opening),
+                format!("{}kr", balance.credit),
+                format!("{}kr", balance.debit),
+                format!("{}kr", balance.closing()),
+            ]
+            .into_iter()
+            .fold(table.row(), |row, cell| {
+                row.element(
+                    Paragraph::new(cell)
+                        .aligned(Alignment::Right)
+                        .padded(3)
+                        .framed(),
+                )
+            })
+            .push()
+            .expect("Table row failed");
+        }
+
+        ["", "Totalt", "", "", "", &format!("{}kr", self.total())]
+            .into_iter()
+            .fold(table.row(), |row, cell| {
+                row.element(
+                    Paragraph::new(cell)
+                        .aligned(Alignment::Right)
+                        .padded(3)
+                        .framed(),
+                )
+            })
+            .push()
+            .expect("Table row failed");
+
+        doc.push(table.padded(10));
+
+        let mut path = path.into();
+        path.push(if self.from == self.to {
+            format!("saldo_{}.pdf", self.from.format("%F"))
+        } else {
+            format!(
+                "saldo_{}_{}.pdf",
+                self.from.format("%F"),
+                self.to.format("%F")
+            )
+        });
+        doc.render_to_file(path.clone())
+            .map_err(|e| Error::Pdf(Arc::new(e)))?;
+
+        Ok(path)
+    }
+}
@@ -1,7 +1,12 @@
 use {
     crate::{
+        events::Event,
         items::Item,
-        receipts::{Payment, Receipt, print::FONT},
+        receipts::{
+            Payment, Receipt,
+            print::{FONT, logo},
+        },
+        settings::Settings,
         summary::Summary,
     },
     chrono::{DateTime, Local},
@@ -60,53 +65,19 @@ impl Stats {
         self,
         path: impl Into<PathBuf>,
         (from, to): (DateTime<Local>, DateTime<Local>),
+        event: Option<&Event>,
+        voucher_liability: i32,
+        settings: &Settings,
     ) -> Result<PathBuf> {
-        let mut doc = Document::new(fonts::FontFamily {
-            regular: FONT.clone(),
-            bold: FONT.clone(),
-            italic: FONT.clone(),
-            bold_italic: FONT.clone(),
-        });
-        doc.set_paper_size((297, 210));
-
-        doc.set_page_decorator({
-            let mut dec = SimplePageDecorator::new();
-            dec.set_margins(10);
-            dec.set_header(|_| {
-                let mut header = TableLayout::new(vec![1, 1]);
-                header
-                    .row()
-                    .element({
-                        let logga = Cursor::new(include_bytes!("../../../resources/logga.png"));
-                        Image::from_reader(logga)
-                            .unwrap()
-                            .with_alignment(Alignment::Left)
-                            .with_scale((0.5, 0.5))
-                    })
-                    .element({
-                        ["Smålands Nation", "Nyhemsgatan 30", "302 49 Halmstad"]
-                            .into_iter()
-                            .map(|s| Paragraph::new(s).aligned(Alignment::Right))
-                            .fold(LinearLayout::vertical(), |list, text| list.element(text))
-                    })
-                    .push()
-                    .expect("Couldn't table header");
-                header
-            });
-            dec
-        });
+        let mut doc = new_document(settings)?;
 
         doc.push(Break::new(1));
 
         doc.push(
-            Text::new(format!(
-                "Försäljning {}",
-                if from == to {
-                    from.format("%F").to_string()
-                } else {
-                    format!("för perioden {} tom {}", from.format("%F"), to.format("%F"))
-                }
-            ))
+            Text::new(match event {
+                Some(event) => format!("Försäljning {}", event.name()),
+                None => format!("Försäljning {}", period(from, to)),
+            })
             .styled(Style::new().with_font_size(24)),
         );
 
@@ -193,11 +164,22 @@ impl Stats {
 
         doc.push(table.padded(10));
 
+        if voucher_liability != 0 {
+            doc.push(Break::new(1));
+            doc.push(Text::new(format!(
+                "Utestående presentkort: {voucher_liability}kr"
+            )));
+        }
+
         let mut path = path.into();
-        path.push(if from == to {
-            format!("{}.pdf", from.format("%F"))
-        } else {
-            format!("{}_{}.pdf", from.format("%F"), to.format("%F"))
+        path.push(match event {
+            Some(event) => format!(
+                "{}_{}.pdf",
+                from.format("%F"),
+                event.name().replace(|c: char| !c.is_alphanumeric(), "_")
+            ),
+            None if from == to => format!("{}.pdf", from.format("%F")),
+            None => format!("{}_{}.pdf", from.format("%F"), to.format("%F")),
         });
         doc.render_to_file(path.clone())
             .map_err(|e| Error::Pdf(Arc::new(e)))?;
@@ -206,11 +188,78 @@ impl Stats {
     }
 }
 
-#[cfg(not(debug_assertions))]
-pub async fn save(Summary { from, to, data }: &Summary) -> Result<PathBuf> {
+//A4 landscape with logo and header lines in the header, shared by all reports
+pub(crate) fn new_document(settings: &Settings) -> Result<Document> {
+    let mut doc = Document::new(fonts::FontFamily {
+        regular: FONT.clone(),
+        bold: FONT.clone(),
+        italic: FONT.clone(),
+        bold_italic: FONT.clone(),
+    });
+    doc.set_paper_size((297, 210));
+
+    //Checked here since the header can not fail
+    let logga = logo(settings).map_err(|e| Error::Io(e.kind()))?;
+    Image::from_reader(Cursor::new(logga.clone())).map_err(|e| Error::Pdf(Arc::new(e)))?;
+    let lines = settings
+        .header()
+        .iter()
+        .cloned()
+        .chain(
+            settings
+                .org_number()
+                .as_ref()
+                .map(|org| format!("Org.nr {org}")),
+        )
+        .collect::<Vec<_>>();
+
+    doc.set_page_decorator({
+        let mut dec = SimplePageDecorator::new();
+        dec.set_margins(10);
+        dec.set_header(move |_| {
+            let mut header = TableLayout::new(vec![1, 1]);
+            header
+                .row()
+                .element({
+                    Image::from_reader(Cursor::new(logga.clone()))
+                        .unwrap()
+                        .with_alignment(Alignment::Left)
+                        .with_scale((0.5, 0.5))
+                })
+                .element({
+                    lines
+                        .iter()
+                        .map(|s| Paragraph::new(s.clone()).aligned(Alignment::Right))
+                        .fold(LinearLayout::vertical(), |list, text| list.element(text))
+                })
+                .push()
+                .expect("Couldn't table header");
+            header
+        });
+        dec
+    });
+
+    Ok(doc)
+}
+
+pub(crate) fn period(from: DateTime<Local>, to: DateTime<Local>) -> String {
+    if from == to {
+        from.format("%F").to_string()
+    } else {
+        format!("för perioden {} tom {}", from.format("%F"), to.format("%F"))
+    }
+}
+
+pub(crate) fn report_dir(settings: &Settings, to: DateTime<Local>) -> Result<PathBuf> {
+    match settings.export_dir() {
+        Some(dir) => year_dir(PathBuf::from(dir), to),
+        None => default_report_dir(to),
+    }
+}
+
+fn year_dir(mut path: PathBuf, to: DateTime<Local>) -> Result<PathBuf> {
     use chrono::Datelike;
 
-    let mut path = dirs::document_dir().ok_or(Error::NoPath)?;
     path.push("sales");
     path.push(to.year().to_string());
     if let Err(e) = std::fs::create_dir_all(&path) {
@@ -220,12 +269,36 @@ pub async fn save(Summary { from, to, data }: &Summary) -> Result<PathBuf> {
         }
     }
 
-    Stats::new(data).create_pdf(path, (*from, *to))
+    Ok(path)
+}
+
+#[cfg(not(debug_assertions))]
+fn default_report_dir(to: DateTime<Local>) -> Result<PathBuf> {
+    year_dir(dirs::document_dir().ok_or(Error::NoPath)?, to)
 }
 
 #[cfg(debug_assertions)]
-pub async fn save(Summary { from, to, data }: &Summary) -> Result<PathBuf> {
-    Stats::new(data).create_pdf(".", (*from, *to))
+fn default_report_dir(_: DateTime<Local>) -> Result<PathBuf> {
+    Ok(PathBuf::from("."))
+}
+
+pub async fn save(
+    Summary {
+        from,
+        to,
+        event,
+        voucher_liability,
+        data,
+    }: &Summary,
+    settings: &Settings,
+) -> Result<PathBuf> {
+    Stats::new(data).create_pdf(
+        report_dir(settings, *to)?,
+        (*from, *to),
+        event.as_ref(),
+        *voucher_liability,
+        settings,
+    )
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,370 @@
+use {
+    crate::{
+        Error, Result,
+        http::{self, Client},
+        items::{Item, images},
+        receipts::{self, Receipt},
+        remote,
+    },
+    chrono::{DateTime, Local},
+    getset::Getters,
+    rusqlite::{Connection, OptionalExtension, params},
+    serde::{Deserialize, Serialize},
+    std::{
+        collections::{HashMap, HashSet},
+        sync::{Arc, Mutex, OnceLock},
+        time::Duration,
+    },
+};
+
+//Offline first, sales and menu changes are stored locally and queued in the outbox. When the
+//central register server can be reached the outbox is sent in order and the menu is read back.
+//  Receipts are stored once on the server however many times they are sent, by register and time
+//  and with the number they got on the register
+//  The latest menu edit wins, by the time it was made on the register and not when it arrived
+//  Items with changes left in the outbox are not overwritten by the server until they are sent
+//  Changes the server refuses are kept aside with the reason until they are sent again or dropped
+const INTERVAL: Duration = Duration::from_secs(30);
+
+static CENTRAL: OnceLock<Client> = OnceLock::new();
+//When the last sync went through and why the last one failed
+static LAST: Mutex<(Option<DateTime<Local>>, Option<String>)> = Mutex::new((None, None));
+
+//Shown in the GUI so problems are noticed while the registers still work
+#[derive(Debug, Clone, Getters)]
+#[getset(get = "pub")]
+pub struct Status {
+    synced: Option<DateTime<Local>>,
+    error: Option<String>,
+    //Changes waiting to be sent
+    pending: usize,
+    rejected: Vec<Rejected>,
+}
+
+//Change the server refused, it is not sent again until retried
+#[derive(Debug, Clone, Getters)]
+#[getset(get = "pub")]
+pub struct Rejected {
+    id: i64,
+    //The receipt or item it is about
+    change: String,
+    reason: String,
+}
+
+//What is queued in the outbox and sent to the server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) enum Change {
+    Receipt(Receipt),
+    Item {
+        //Name before the change so renamed items are found on the server
+        name: String,
+        //None when it was deleted
+        item: Option<Item>,
+        modified: DateTime<Local>,
+        //The picture itself since its id is only known in this database
+        #[serde(default)]
+        image: Option<Vec<u8>>,
+    },
+}
+
+//Receipts are told apart by register on the server, so a name has to be set first
+pub fn connect(address: &str, key: &str) -> Result<()> {
+    if receipts::REGISTER.get().is_none() {
+        return Err(Error::NoRegister);
+    }
+    CENTRAL
+        .set(Client::new(address).with_key(key))
+        .map_err(|_| Error::AlreadyConnected)
+}
+
+//Connects and syncs in the background for as long as the app runs
+pub fn start(address: &str, key: &str) -> Result<()> {
+    connect(address, key)?;
+    tokio::spawn(async {
+        loop {
+            //Failures are kept for the status and tried again next time
+            let _ = sync().await;
+            tokio::time::sleep(INTERVAL).await;
+        }
+    });
+    Ok(())
+}
+
+pub(crate) fn is_enabled() -> bool {
+    CENTRAL.get().is_some()
+}
+
+fn push(conn: &Connection, change: &Change) -> Result<()> {
+    let (kind, payload) = match change {
+        Change::Receipt(_) => ("receipt", serde_json::to_string(change)),
+        Change::Item { .. } => ("item", serde_json::to_string(change)),
+    };
+    let payload = payload.map_err(|e| http::Error::Json(Arc::new(e)))?;
+    conn.prepare_cached("INSERT INTO outbox (kind, payload) VALUES (?1, ?2)")?
+        .execute(params![kind, payload])?;
+    Ok(())
+}
+
+//Queued in the same transaction as the sale so it is sent if and only if it was stored
+pub(crate) fn receipt_sold(conn: &Connection, receipt: &Receipt) -> Result<()> {
+    if !is_enabled() {
+        return Ok(());
+    }
+    push(conn, &Change::Receipt(receipt.clone()))
+}
+
+//Stamped even when not syncing since the server may be running on this database, queued as
+//the item is now or None if it was deleted
+pub(crate) async fn item_changed(name: &str, new_name: Option<&str>) -> Result<()> {
+    let modified = Local::now();
+    if let Some(new_name) = new_name {
+        insert!(
+            "UPDATE menu SET modified=?1 WHERE name=?2",
+            params![modified, new_name]
+        )?;
+    }
+    if !is_enabled() {
+        return Ok(());
+    }
+    let item = match new_name {
+        Some(new_name) => Item::get(new_name).await?,
+        None => None,
+    };
+    let image = match item.as_ref().and_then(|item| *item.image()) {
+        Some(id) => images::get(id).await?,
+        None => None,
+    };
+    let change = Change::Item {
+        name: name.to_string(),
+        item,
+        modified,
+        image,
+    };
+    let conn = crate::CONNECTION
+        .get()
+        .ok_or(Error::NotConnected)?
+        .lock()
+        .await;
+    push(&conn, &change)
+}
+
+//Changes not yet sent to the server, rejected ones not included
+pub async fn pending() -> Result<usize> {
+    select!(
+        "SELECT COUNT(*) FROM outbox WHERE rejected IS NULL",
+        |row| row.get(0),
+        ..
+    )
+    .next()
+    .unwrap_or(Ok(0))
+}
+
+//None when this register does not sync
+pub async fn status() -> Result<Option<Status>> {
+    if !is_enabled() {
+        return Ok(None);
+    }
+    let rejected = select!(
+        "SELECT id, payload, rejected FROM outbox WHERE rejected IS NOT NULL ORDER BY id",
+        |row| {
+            let payload: String = row.get("payload")?;
+            Ok(Rejected {
+                id: row.get("id")?,
+                change: match serde_json::from_str(&payload) {
+                    Ok(Change::Receipt(receipt)) => format!("Kvitto {}", receipt.reference()),
+                    Ok(Change::Item { name, .. }) => format!("Artikel {name}"),
+                    Err(_) => payload,
+                },
+                reason: row.get("rejected")?,
+            })
+        }
+    )?;
+    let (synced, error) = LAST.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    Ok(Some(Status {
+        synced,
+        error,
+        pending: pending().await?,
+        rejected,
+    }))
+}
+
+//Sent again with the next sync, e.g. once the item it refers to exists on the server
+pub async fn retry(id: i64) -> Result<()> {
+    insert!("UPDATE outbox SET rejected=NULL WHERE id=?1", params![id])?;
+    Ok(())
+}
+
+//Gives up on a rejected change, it stays in the local database only. An item is marked as never
+//synced so the next pull does not take it for one deleted by another register
+pub async fn discard(id: i64) -> Result<()> {
+    let mut conn = crate::CONNECTION
+        .get()
+        .ok_or(Error::NotConnected)?
+        .lock()
+        .await;
+    let tx = conn.transaction()?;
+    let payload: Option<String> = tx
+        .prepare_cached("SELECT payload FROM outbox WHERE id=?1 AND rejected IS NOT NULL")?
+        .query_row(params![id], |row| row.get("payload"))
+        .optional()?;
+    if let Some(Ok(Change::Item {
+        item: Some(item), ..
+    })) = payload.map(|payload| serde_json::from_str(&payload))
+    {
+        tx.prepare_cached("UPDATE menu SET modified=NULL WHERE name=?1")?
+            .execute(params![item.name()])?;
+    }
+    tx.prepare_cached("DELETE FROM outbox WHERE id=?1 AND rejected IS NOT NULL")?
+        .execute(params![id])?;
+    tx.commit()?;
+    Ok(())
+}
+
+//Sends the outbox and then reads the menu back, the outcome is kept for the status
+pub async fn sync() -> Result<()> {
+    let result = send_and_pull().await;
+    let mut last = LAST.lock().unwrap_or_else(|e| e.into_inner());
+    match &result {
+        Ok(()) => *last = (Some(Local::now()), None),
+        Err(e) => last.1 = Some(format!("{e:?}")),
+    }
+    result
+}
+
+//Stops at the first change that can not be sent since the server could not be reached
+async fn send_and_pull() -> Result<()> {
+    let client = CENTRAL.get().ok_or(Error::NotConnected)?;
+
+    let outbox: Vec<(i64, String)> = select!(
+        "SELECT id, payload FROM outbox WHERE rejected IS NULL ORDER BY id",
+        |row| Ok((row.get("id")?, row.get("payload")?))
+    )?;
+    for (id, payload) in outbox {
+        match remote::send::<()>(client, "/sync/push", payload).await {
+            Ok(()) => insert!("DELETE FROM outbox WHERE id=?1", params![id])?,
+            //Refused by the server, it would never get through so it is set aside instead of
+            //blocking everything after it
+            Err(Error::Refused(reason)) => insert!(
+                "UPDATE outbox SET rejected=?2 WHERE id=?1",
+                params![id, reason]
+            )?,
+            //Offline, a wrong key or the server busy, the rest is sent in order next time
+            Err(e) => return Err(e),
+        };
+    }
+
+    pull(client).await
+}
+
+async fn pull(client: &Client) -> Result<()> {
+    let central: Vec<(Item, Option<DateTime<Local>>)> =
+        remote::send(client, "/sync/menu", String::from("null")).await?;
+    let local: HashMap<String, Option<DateTime<Local>>> = Item::get_synced()
+        .await?
+        .into_iter()
+        .map(|(item, modified)| (item.name().clone(), modified))
+        .collect();
+    let pending = pending_items().await?;
+
+    //An item that can not be stored, e.g. since a change still in the outbox here took its barcode,
+    //does not stop the rest of the menu. The first failure is returned once all are tried
+    let mut failed = Ok(());
+    for (item, modified) in central.iter() {
+        //Never changed since the databases were copied
+        let Some(modified) = *modified else {
+            continue;
+        };
+        let newer = match local.get(item.name()) {
+            Some(Some(local)) => modified > *local,
+            _ => true,
+        };
+        if newer && !pending.contains(item.name()) {
+            let name = item.name().clone();
+            //The picture is read apart from the menu and only for the items that changed
+            let stored = async {
+                let image = match *item.image() {
+                    Some(id) => remote::send(client, "/items/image", id.to_string()).await?,
+                    None => None,
+                };
+                item.clone().store(&name, modified, image).await
+            }
+            .await;
+            if let Err(e) = stored {
+                failed = failed.and(Err(e));
+            }
+        }
+    }
+
+    //Changed here before but no longer on the server, so it was deleted by another register
+    for (name, modified) in local {
+        if modified.is_some()
+            && !pending.contains(&name)
+            && !central.iter().any(|(item, _)| *item.name() == name)
+        {
+            Item::remove(&name).await?;
+        }
+    }
+    failed
+}
+
+//Names before and after every item change in the outbox
+async fn pending_items() -> Result<HashSet<String>> {
+    let payloads: Vec<String> = select!(
+        "SELECT payload FROM outbox WHERE kind = 'item' ORDER BY id",
+        |row| row.get("payload")
+    )?;
+    let mut names = HashSet::new();
+    for payload in payloads {
+        if let Ok(Change::Item { name, item, .. }) = serde_json::from_str(&payload) {
+            names.insert(name);
+            names.extend(item.map(|item| item.name().clone()));
+        }
+    }
+    Ok(names)
+}
+
+//Run by the server for each change sent to it. Only changes that could never be stored are
+//refused, anything else is sent again by the register
+pub(crate) async fn receive(change: serde_json::Value) -> Result<()> {
+    let change: Change =
+        serde_json::from_value(change).map_err(|e| Error::Refused(e.to_string()))?;
+    apply(change).await.map_err(|e| match e {
+        Error::Sqlite(ref sqlite)
+            if sqlite.sqlite_error_code() == Some(rusqlite::ErrorCode::ConstraintViolation) =>
+        {
+            Error::Refused(format!("{e:?}"))
+        }
+        Error::ImageTooLarge | Error::InvalidImage => Error::Refused(format!("{e:?}")),
+        e => e,
+    })
+}
+
+async fn apply(change: Change) -> Result<()> {
+    match change {
+        Change::Receipt(receipt) => receipt.upload().await,
+        Change::Item {
+            name,
+            item,
+            modified,
+            image,
+        } => {
+            let stored: Option<DateTime<Local>> = select!(
+                "SELECT modified FROM menu WHERE name=?1",
+                params![name],
+                |row| row.get("modified"),
+                ..
+            )
+            .next()
+            .transpose()?
+            .flatten();
+            //Made before the change the server already has
+            if stored.is_some_and(|stored| stored > modified) {
+                return Ok(());
+            }
+            match item {
+                Some(item) => item.store(&name, modified, image).await,
+                None => Item::remove(&name).await,
+            }
+        }
+    }
+}
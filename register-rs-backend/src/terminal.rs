@@ -0,0 +1,42 @@
+use {
+    crate::Result,
+    std::{future::Future, pin::Pin, time::Duration},
+};
+
+pub mod http;
+pub mod mock;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T>> + Send + 'a>>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PaymentState {
+    Pending,
+    Confirmed,
+    Cancelled,
+    Failed(String),
+}
+
+//Card terminals and the like, the guest confirms the payment on the terminal some time after it
+//is requested so the sale is only stored once the provider reports it as confirmed
+pub trait PaymentProvider: Send + Sync {
+    //Asks the guest to pay, returns the id the payment is followed with
+    fn request(&self, amount: i32, reference: &str) -> BoxFuture<'_, String>;
+    fn status(&self, id: &str) -> BoxFuture<'_, PaymentState>;
+    fn cancel(&self, id: &str) -> BoxFuture<'_, ()>;
+}
+
+//"mock" for testing without a terminal, anything else is the address of an HTTP adapter
+pub fn provider(terminal: &str) -> Box<dyn PaymentProvider> {
+    match terminal {
+        "mock" => Box::new(mock::Mock),
+        address => Box::new(http::Http::new(address)),
+    }
+}
+
+//Meant to be called until the payment is no longer pending
+pub async fn poll(terminal: &str, id: &str) -> Result<PaymentState> {
+    tokio::time::sleep(POLL_INTERVAL).await;
+    provider(terminal).status(id).await
+}
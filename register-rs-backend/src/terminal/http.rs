@@ -0,0 +1,73 @@
+use {
+    super::{BoxFuture, PaymentProvider, PaymentState},
+    crate::http::{Client, Error, parse},
+    serde::{Deserialize, Serialize},
+    std::sync::Arc,
+};
+
+//Adapter for terminals behind a small local HTTP service speaking JSON
+//  POST   /payments       {"amount": 100, "reference": "..."} -> {"id": "..."}
+//  GET    /payments/{id}  -> {"state": "pending|confirmed|cancelled|failed", "message": "..."}
+//  DELETE /payments/{id}
+pub struct Http {
+    client: Client,
+}
+
+#[derive(Serialize)]
+struct Request<'a> {
+    amount: i32,
+    reference: &'a str,
+}
+
+#[derive(Deserialize)]
+struct Created {
+    id: String,
+}
+
+#[derive(Deserialize)]
+struct Status {
+    state: String,
+    #[serde(default)]
+    message: String,
+}
+
+impl Http {
+    pub fn new(address: &str) -> Self {
+        Self {
+            client: Client::new(address),
+        }
+    }
+}
+
+impl PaymentProvider for Http {
+    fn request(&self, amount: i32, reference: &str) -> BoxFuture<'_, String> {
+        let body = serde_json::to_string(&Request { amount, reference });
+        Box::pin(async move {
+            let body = body.map_err(|e| Error::Json(Arc::new(e)))?;
+            let created: Created =
+                parse(&self.client.call("POST", "/payments", Some(body)).await?)?;
+            Ok(created.id)
+        })
+    }
+
+    fn status(&self, id: &str) -> BoxFuture<'_, PaymentState> {
+        let path = format!("/payments/{id}");
+        Box::pin(async move {
+            let status: Status = parse(&self.client.call("GET", &path, None).await?)?;
+            Ok(match status.state.as_str() {
+                "pending" => PaymentState::Pending,
+                "confirmed" => PaymentState::Confirmed,
+                "cancelled" => PaymentState::Cancelled,
+                _ => PaymentState::Failed(status.message),
+            })
+        })
+    }
+
+    fn cancel(&self, id: &str) -> BoxFuture<'_, ()> {
+        let path = format!("/payments/{id}");
+        Box::pin(async move {
+            self.client.call("DELETE", &path, None).await?;
+            Ok(())
+        })
+    }
+}
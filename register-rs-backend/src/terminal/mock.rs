@@ -0,0 +1,59 @@
+use {
+    super::{BoxFuture, PaymentProvider, PaymentState},
+    std::{
+        collections::HashMap,
+        sync::{LazyLock, Mutex},
+        time::{Duration, Instant},
+    },
+};
+
+const DELAY: Duration = Duration::from_secs(3);
+
+static PAYMENTS: LazyLock<Mutex<HashMap<String, (Instant, PaymentState)>>> =
+    LazyLock::new(Default::default);
+
+//Confirms every payment a few seconds after it was requested
+pub struct Mock;
+
+impl PaymentProvider for Mock {
+    fn request(&self, _: i32, _: &str) -> BoxFuture<'_, String> {
+        Box::pin(async move {
+            let id = crate::random_code(8);
+            PAYMENTS
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .insert(id.clone(), (Instant::now(), PaymentState::Pending));
+            Ok(id)
+        })
+    }
+
+    fn status(&self, id: &str) -> BoxFuture<'_, PaymentState> {
+        let id = id.to_string();
+        Box::pin(async move {
+            let mut payments = PAYMENTS.lock().unwrap_or_else(|e| e.into_inner());
+            Ok(match payments.get_mut(&id) {
+                Some((requested, state)) => {
+                    if *state == PaymentState::Pending && requested.elapsed() >= DELAY {
+                        *state = PaymentState::Confirmed;
+                    }
+                    state.clone()
+                }
+                None => PaymentState::Failed(format!("Okänd betalning {id}")),
+            })
+        })
+    }
+
+    fn cancel(&self, id: &str) -> BoxFuture<'_, ()> {
+        let id = id.to_string();
+        Box::pin(async move {
+            if let Some((_, state @ PaymentState::Pending)) = PAYMENTS
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .get_mut(&id)
+            {
+                *state = PaymentState::Cancelled;
+            }
+            Ok(())
+        })
+    }
+}
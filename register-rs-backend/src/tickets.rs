@@ -0,0 +1,102 @@
+use crate::Result;
+use chrono::{DateTime, Local};
+use getset::Getters;
+use rusqlite::{Connection, params};
+use serde::{Deserialize, Serialize};
+
+const NUMBER_LEN: usize = 12;
+
+//Entry ticket, the number is printed as a QR code on the receipt
+#[derive(Debug, Default, Clone, PartialEq, Eq, Getters, Serialize, Deserialize)]
+#[getset(get = "pub")]
+pub struct Ticket {
+    number: String,
+    item: String,
+    sold: DateTime<Local>,
+    used: Option<DateTime<Local>>,
+}
+
+//Result of scanning a ticket at the door
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Validation {
+    Valid(Ticket),
+    AlreadyUsed(Ticket),
+    Unknown(String),
+}
+
+impl Ticket {
+    pub(crate) fn new(item: String, sold: DateTime<Local>) -> Self {
+        Self {
+            number: crate::random_code(NUMBER_LEN),
+            item,
+            sold,
+            used: None,
+        }
+    }
+
+    fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            number: row.get("number")?,
+            item: row.get("item")?,
+            sold: row.get("receipt")?,
+            used: row.get("used")?,
+        })
+    }
+
+    //Tickets sold the last day, most recently scanned first
+    pub async fn get_recents() -> Result<Vec<Self>> {
+        remote!("/tickets");
+        select!(
+            "SELECT number, item, receipt, used FROM tickets
+                WHERE receipt > date('now','-1 day')
+                ORDER BY used IS NULL, used DESC, receipt DESC",
+            Self::from_row
+        )
+    }
+
+    pub(crate) fn insert(&self, conn: &Connection) -> Result<()> {
+        conn.prepare_cached(
+            "INSERT INTO tickets (number, item, receipt, event)
+                VALUES (?1, ?2, ?3, (SELECT id FROM events WHERE active))",
+        )?
+        .execute(params![self.number, self.item, self.sold])?;
+        Ok(())
+    }
+
+    //Sold on another register, the event is found by the time as for the receipt it came with
+    pub(crate) fn upload(&self, conn: &Connection) -> Result<()> {
+        conn.prepare_cached(
+            "INSERT INTO tickets (number, item, receipt, event, used)
+                VALUES (?1, ?2, ?3,
+                    (SELECT id FROM events WHERE ?3 BETWEEN start_time AND end_time LIMIT 1), ?4)",
+        )?
+        .execute(params![self.number, self.item, self.sold, self.used])?;
+        Ok(())
+    }
+
+    //Marked as used in the same statement as it is checked so a ticket only gets in once
+    pub async fn scan(number: String) -> Result<Validation> {
+        remote!("/tickets/scan", number);
+        let number = number.trim().to_uppercase();
+        let now = Local::now();
+        let marked = insert!(
+            "UPDATE tickets SET used=?2 WHERE number=?1 AND used IS NULL",
+            params![number, now]
+        )?;
+
+        let ticket = select!(
+            "SELECT number, item, receipt, used FROM tickets WHERE number=?1",
+            params![number],
+            Self::from_row,
+            ..
+        )
+        .next()
+        .transpose()?;
+
+        Ok(match ticket {
+            Some(ticket) if marked > 0 => Validation::Valid(ticket),
+            Some(ticket) => Validation::AlreadyUsed(ticket),
+            None => Validation::Unknown(number),
+        })
+    }
+}
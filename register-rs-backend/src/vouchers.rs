@@ -0,0 +1,124 @@
+use crate::{Result, settings::Settings};
+use chrono::{DateTime, Local, NaiveDate};
+use getset::{Getters, WithSetters};
+use rusqlite::{Connection, params};
+use serde::{Deserialize, Serialize};
+
+pub(crate) mod print;
+
+const CODE_LEN: usize = 10;
+
+//Gift voucher, can be used over several receipts until it runs out or expires
+#[derive(Debug, Default, Clone, PartialEq, Eq, Getters, WithSetters, Serialize, Deserialize)]
+#[getset(get = "pub", set_with = "pub")]
+pub struct Voucher {
+    code: String,
+    value: i32,
+    issued: DateTime<Local>,
+    expires: NaiveDate,
+    //Value minus all redemptions
+    remaining: i32,
+}
+
+impl Voucher {
+    pub fn new(value: i32, expires: NaiveDate) -> Self {
+        Self {
+            code: crate::random_code(CODE_LEN),
+            value,
+            issued: Local::now(),
+            expires,
+            remaining: value,
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.remaining > 0 && self.expires >= Local::now().date_naive()
+    }
+
+    fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            code: row.get("code")?,
+            value: row.get("value")?,
+            issued: row.get("issued")?,
+            expires: row.get("expires")?,
+            remaining: row.get("remaining")?,
+        })
+    }
+
+    //Vouchers that can still be used
+    pub async fn get_outstanding() -> Result<Vec<Self>> {
+        remote!("/vouchers");
+        select!(
+            "SELECT vouchers.code, vouchers.value, vouchers.issued, vouchers.expires,
+                    vouchers.value - COALESCE(SUM(voucher_redemptions.amount), 0) AS remaining
+                FROM vouchers
+                    LEFT JOIN voucher_redemptions ON voucher_redemptions.code = vouchers.code
+                WHERE vouchers.expires >= ?1
+                GROUP BY vouchers.code
+                HAVING remaining > 0
+                ORDER BY vouchers.issued",
+            params![Local::now().date_naive()],
+            Self::from_row,
+            _
+        )
+    }
+
+    //Value of vouchers that were issued but not used or expired at the given time
+    pub async fn liability(at: DateTime<Local>) -> Result<i32> {
+        remote!("/vouchers/liability", at);
+        select!(
+            "SELECT COALESCE(SUM(value - COALESCE((
+                    SELECT SUM(amount) FROM voucher_redemptions
+                        WHERE voucher_redemptions.code = vouchers.code AND time <= ?1
+                ), 0)), 0)
+                FROM vouchers
+                WHERE issued <= ?1 AND expires >= ?2",
+            params![at, at.date_naive()],
+            |row| row.get(0),
+            ..
+        )
+        .next()
+        .unwrap_or(Ok(0))
+    }
+
+    pub async fn issue(self) -> Result<Self> {
+        remote!("/vouchers/issue", self);
+        insert!(
+            "INSERT INTO vouchers (code, value, issued, expires) VALUES (?1, ?2, ?3, ?4)",
+            params![self.code, self.value, self.issued, self.expires]
+        )?;
+        Ok(self)
+    }
+
+    pub async fn print(&self) -> Result<()> {
+        Ok(print::print(self, &Settings::get().await?).await?)
+    }
+
+    //Checked and written in one statement, what is left stays on the voucher. A negative amount
+    //would add value to it
+    pub(crate) fn redeem(
+        conn: &Connection,
+        code: &str,
+        time: DateTime<Local>,
+        amount: i32,
+    ) -> Result<()> {
+        if amount <= 0 {
+            return Err(crate::Error::InvalidAmount);
+        }
+        let redeemed = conn
+            .prepare_cached(
+                "INSERT INTO voucher_redemptions (code, time, amount)
+                    SELECT code, ?2, ?3 FROM vouchers
+                    WHERE code=?1
+                        AND expires >= ?4
+                        AND value - (SELECT COALESCE(SUM(amount), 0) FROM voucher_redemptions
+                            WHERE code=?1) >= ?3",
+            )?
+            .execute(params![code, time, amount, time.date_naive()])?;
+        if redeemed == 0 {
+            Err(crate::Error::VoucherNotValid)
+        } else {
+            Ok(())
+        }
+    }
+}
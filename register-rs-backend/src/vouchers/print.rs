@@ -0,0 +1,56 @@
+use {
+    super::Voucher,
+    crate::{
+        print_queue,
+        receipts::print::{Error, RECEIPT_PATH, Result, new_document, rule},
+        settings::Settings,
+    },
+    genpdf::{
+        Alignment, Element,
+        elements::{Break, Paragraph},
+        style::Style,
+    },
+    std::{path::PathBuf, sync::Arc},
+};
+
+fn create_pdf(path: impl Into<PathBuf>, voucher: &Voucher, settings: &Settings) -> Result<PathBuf> {
+    let mut doc = new_document(settings)?;
+
+    doc.push(Break::new(1));
+    doc.push(
+        Paragraph::new("PRESENTKORT")
+            .aligned(Alignment::Center)
+            .styled(Style::new().with_font_size(18)),
+    );
+    doc.push(
+        Paragraph::new(format!("{}kr", voucher.value))
+            .aligned(Alignment::Center)
+            .styled(Style::new().with_font_size(18)),
+    );
+    doc.push(Break::new(1));
+    doc.push(Paragraph::new(voucher.code.clone()).aligned(Alignment::Center));
+    doc.push(Break::new(1));
+    doc.push(
+        Paragraph::new(format!("Giltigt t.o.m. {}", voucher.expires.format("%F")))
+            .aligned(Alignment::Center),
+    );
+    doc.push(rule(settings));
+    doc.push(Paragraph::new(format!(
+        "Utfärdat {}",
+        voucher.issued.format("%F %T")
+    )));
+    doc.push(Break::new(1));
+
+    let mut path = path.into();
+    path.push(format!("voucher_{}.pdf", voucher.code));
+    doc.render_to_file(path.clone())
+        .map_err(|e| Error::Pdf(Arc::new(e)))?;
+
+    Ok(path)
+}
+
+pub async fn print(voucher: &Voucher, settings: &Settings) -> Result<()> {
+    let path = RECEIPT_PATH.get().ok_or(Error::NoPrintPath)?;
+    print_queue::push(settings, create_pdf(path, voucher, settings)?, None);
+    Ok(())
+}
@@ -0,0 +1,275 @@
+//Two registers with their own databases syncing through the server binary. The backend keeps one
+//connection per process, so every register step runs this test binary again as the register test
+//with the database and actions in the environment
+use {
+    chrono::Local,
+    register_rs_backend::{
+        items::Item,
+        receipts::{Payment, Receipt},
+        sync,
+    },
+    rusqlite::{Connection, params},
+    std::{
+        env,
+        net::{TcpListener, TcpStream},
+        path::{Path, PathBuf},
+        process::{Child, Command},
+        thread,
+        time::Duration,
+    },
+};
+
+const KEY: &str = "test-key";
+
+//Kills the server and removes the databases when the test ends, also when it fails
+struct Setup {
+    dir: PathBuf,
+    server: Option<Child>,
+    address: String,
+}
+
+impl Drop for Setup {
+    fn drop(&mut self) {
+        if let Some(server) = self.server.as_mut() {
+            let _ = server.kill();
+            let _ = server.wait();
+        }
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+impl Setup {
+    fn new() -> Self {
+        let dir = env::temp_dir().join(format!("register-sync-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        Self {
+            dir,
+            server: None,
+            address: format!("127.0.0.1:{port}"),
+        }
+    }
+
+    fn db(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{name}.db"))
+    }
+
+    //Every register starts from a copy of the same database, as in the readme
+    fn copy(&self, from: &str, to: &str) {
+        std::fs::copy(self.db(from), self.db(to)).unwrap();
+    }
+
+    fn start_server(&mut self) {
+        let server = Command::new(env!("CARGO_BIN_EXE_server"))
+            .args([&self.address, self.db("central").to_str().unwrap()])
+            .env("SMALANDS_SERVER_KEY", KEY)
+            .env("XDG_CONFIG_HOME", &self.dir)
+            .env("HOME", &self.dir)
+            .spawn()
+            .unwrap();
+        self.server = Some(server);
+        for _ in 0..100 {
+            if TcpStream::connect(&self.address).is_ok() {
+                return;
+            }
+            thread::sleep(Duration::from_millis(50));
+        }
+        panic!("Server did not start");
+    }
+
+    //Runs the actions as the register with the same name as its database
+    fn register(&self, name: &str, actions: &[&str]) {
+        let status = Command::new(env::current_exe().unwrap())
+            .args(["register", "--exact", "--nocapture", "--test-threads=1"])
+            .env("SYNC_DB", self.db(name))
+            .env("SYNC_REGISTER", name)
+            .env("SYNC_SERVER", &self.address)
+            .env("SYNC_ACTIONS", actions.join("|"))
+            .status()
+            .unwrap();
+        assert!(status.success(), "{name} failed at {actions:?}");
+    }
+}
+
+fn price(db: &Path, item: &str) -> Option<i32> {
+    Connection::open(db)
+        .unwrap()
+        .query_row(
+            "SELECT price FROM menu WHERE name=?1",
+            params![item],
+            |row| row.get(0),
+        )
+        .ok()
+}
+
+//The picture itself, found through the id the item has in that database
+fn image(db: &Path, item: &str) -> Option<Vec<u8>> {
+    Connection::open(db)
+        .unwrap()
+        .query_row(
+            "SELECT images.image FROM menu INNER JOIN images ON images.id = menu.image
+                WHERE menu.name=?1",
+            params![item],
+            |row| row.get(0),
+        )
+        .ok()
+}
+
+fn count(db: &Path, sql: &str) -> i64 {
+    Connection::open(db)
+        .unwrap()
+        .query_row(sql, [], |row| row.get(0))
+        .unwrap()
+}
+
+#[test]
+fn registers_sync_through_server() {
+    let mut setup = Setup::new();
+    setup.register(
+        "a",
+        &["new:Öl:50", "new:Läsk:20", "new:Chips:15", "new:Cider:40"],
+    );
+    setup.copy("a", "b");
+    setup.copy("a", "central");
+    setup.start_server();
+
+    //Idempotent upload, the answer is lost so the sale is sent twice
+    setup.register("a", &["sell:Öl"]);
+    Connection::open(setup.db("a"))
+        .unwrap()
+        .execute(
+            "INSERT INTO outbox (kind, payload) SELECT kind, payload FROM outbox",
+            [],
+        )
+        .unwrap();
+    setup.register("a", &["sync"]);
+    setup.register("b", &["sell:Öl", "sync"]);
+    let central = setup.db("central");
+    assert_eq!(count(&central, "SELECT COUNT(*) FROM receipts"), 2);
+    assert_eq!(count(&central, "SELECT COUNT(*) FROM receipt_item"), 2);
+    //Both keep the number they got on their own register
+    assert_eq!(
+        count(
+            &central,
+            "SELECT COUNT(*) FROM receipts WHERE number = 1 AND register IN ('a', 'b')"
+        ),
+        2
+    );
+    assert_eq!(count(&setup.db("a"), "SELECT COUNT(*) FROM outbox"), 0);
+
+    //The latest edit wins even when it arrives first
+    setup.register("a", &["price:Öl:60"]);
+    setup.register("b", &["price:Öl:70", "sync"]);
+    setup.register("a", &["sync"]);
+    setup.register("b", &["sync"]);
+    for db in ["a", "b", "central"] {
+        assert_eq!(price(&setup.db(db), "Öl"), Some(70), "{db}");
+    }
+
+    //Pictures are sent along with the item and read back on the other register
+    setup.register("a", &["image:Öl", "sync"]);
+    setup.register("b", &["sync"]);
+    let picture = image(&setup.db("a"), "Öl");
+    assert!(picture.is_some());
+    assert_eq!(image(&central, "Öl"), picture);
+    assert_eq!(image(&setup.db("b"), "Öl"), picture);
+
+    //Deleted everywhere, while a delete made before an edit elsewhere loses to it
+    setup.register("a", &["delete:Läsk", "sync"]);
+    setup.register("a", &["delete:Chips"]);
+    setup.register("b", &["price:Chips:25", "sync"]);
+    setup.register("a", &["sync"]);
+    setup.register("b", &["sync"]);
+    for db in ["a", "b", "central"] {
+        assert_eq!(price(&setup.db(db), "Läsk"), None, "{db}");
+        assert_eq!(price(&setup.db(db), "Chips"), Some(25), "{db}");
+    }
+
+    //Refused by the server since b took the barcode first, so it stays in the outbox and the newer
+    //price from b does not overwrite the edit on a. The sync fails as b's item with the barcode
+    //can not be stored on a either
+    setup.register("b", &["barcode:Öl:123", "sync"]);
+    setup.register("a", &["barcode:Cider:123", "failing-sync"]);
+    setup.register("b", &["price:Cider:48", "sync"]);
+    setup.register("a", &["failing-sync"]);
+    assert_eq!(price(&setup.db("a"), "Cider"), Some(40));
+    assert_eq!(price(&central, "Cider"), Some(48));
+    assert_eq!(
+        count(
+            &setup.db("a"),
+            "SELECT COUNT(*) FROM outbox WHERE rejected IS NOT NULL"
+        ),
+        1
+    );
+}
+
+//Only does something when started by registers_sync_through_server
+#[tokio::test]
+async fn register() {
+    let Ok(actions) = env::var("SYNC_ACTIONS") else {
+        return;
+    };
+    let db = env::var("SYNC_DB").unwrap();
+    register_rs_backend::connect(&db).unwrap();
+    register_rs_backend::set_register(env::var("SYNC_REGISTER").unwrap()).unwrap();
+    //The databases are made before the server is started
+    if !actions.starts_with("new:") {
+        sync::connect(&env::var("SYNC_SERVER").unwrap(), KEY).unwrap();
+    }
+
+    for action in actions.split('|') {
+        let parts: Vec<&str> = action.split(':').collect();
+        let find = |name: &str| {
+            let name = name.to_string();
+            async move {
+                Item::get_all()
+                    .await
+                    .unwrap()
+                    .into_iter()
+                    .find(|item| *item.name() == name)
+                    .unwrap()
+            }
+        };
+        match parts[..] {
+            ["new", name, price] => Item::new()
+                .with_name(name.to_string())
+                .with_price(price.parse().unwrap())
+                .insert_new()
+                .await
+                .unwrap(),
+            ["price", name, price] => {
+                let item = find(name).await;
+                let new = item.clone().with_price(price.parse().unwrap());
+                item.update(new).await.unwrap();
+            }
+            ["barcode", name, barcode] => {
+                let item = find(name).await;
+                let new = item.clone().with_barcode(Some(barcode.to_string()));
+                item.update(new).await.unwrap();
+            }
+            ["image", name] => {
+                let mut png = Vec::new();
+                image::DynamicImage::new_rgba8(2, 2)
+                    .write_to(&mut png, image::ImageOutputFormat::Png)
+                    .unwrap();
+                find(name).await.change_image(Some(png)).await.unwrap();
+            }
+            ["delete", name] => find(name).await.delete().await.unwrap(),
+            ["sell", name] => {
+                let mut receipt = Receipt::default()
+                    .with_time(Local::now())
+                    .with_payment(Payment::Cash);
+                receipt.insert(find(name).await, 1);
+                receipt.insert_sale().await.unwrap();
+            }
+            ["sync"] => sync::sync().await.unwrap(),
+            ["failing-sync"] => assert!(sync::sync().await.is_err()),
+            _ => panic!("Unknown action {action}"),
+        }
+    }
+}
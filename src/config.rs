@@ -18,6 +18,30 @@ pub fn init_db() -> Result<()> {
     }
 }
 
+//Client mode or syncing when a server is set, has to run after init_db and before anything is
+//loaded
+pub async fn connect_server() -> Result<()> {
+    let settings = connect_client().await?;
+    if let Some(server) = settings.sync_server() {
+        let key = settings.server_key().clone().unwrap_or_default();
+        backend::sync::start(server, &key)?;
+    }
+    Ok(())
+}
+
+//Only client mode, for the kitchen display which leaves syncing to the register it runs next to
+pub async fn connect_client() -> Result<backend::settings::Settings> {
+    let settings = backend::settings::Settings::get().await?;
+    if let Some(register) = settings.register() {
+        backend::set_register(register.clone())?;
+    }
+    if let Some(server) = settings.server() {
+        let key = settings.server_key().clone().unwrap_or_default();
+        backend::connect_server(server, &key)?;
+    }
+    Ok(settings)
+}
+
 pub fn set_receipt_path() -> Result<()> {
     //FIXME dbg path
     let mut conf_path = dirs::config_dir().ok_or("No config path")?;
@@ -0,0 +1,132 @@
+use {
+    crate::{
+        screens::{menu::PendingPayment, Message},
+        theme::DEF_PADDING,
+        widgets::{column, padded_column, qr::qr_code, row, BIG_TEXT},
+    },
+    backend::{
+        receipts::{payments::swish_qr, Receipt},
+        settings::Settings,
+    },
+    iced::{
+        widget::{
+            image::{Handle, Image},
+            Column, Container, Rule, Scrollable, Space, Text,
+        },
+        Alignment, Element, Length,
+    },
+};
+
+//Large enough to read from the other side of the bar
+const LINE_TEXT: u16 = 48;
+const TOTAL_TEXT: u16 = 96;
+
+//What the guest sees in the customer window, sent from the menu every time it changes
+#[derive(Debug, Clone, Default)]
+pub struct Display {
+    receipt: Receipt,
+    //Payee and the receipt waiting to be paid with Swish
+    swish: Option<(String, Receipt)>,
+    settings: Settings,
+}
+
+impl Display {
+    pub fn new(receipt: Receipt, swish: Option<(String, Receipt)>, settings: Settings) -> Self {
+        Self {
+            receipt,
+            swish,
+            settings,
+        }
+    }
+
+    //Card payments are kept by the app, so the pending one is passed in
+    pub fn view(&self, payment: Option<&PendingPayment>) -> Element<Message> {
+        let content = match (payment.filter(|p| p.is_pending()), &self.swish) {
+            (Some(payment), _) => padded_column![
+                Text::new(format!("{} kr", payment.receipt().sum())).size(TOTAL_TEXT),
+                Text::new("Betala i kortterminalen").size(LINE_TEXT),
+            ]
+            .align_items(Alignment::Center)
+            .height(Length::Shrink)
+            .into(),
+            (None, Some((payee, receipt))) => self.swish(payee, receipt),
+            (None, None) if self.receipt.is_empty() => self.idle(),
+            (None, None) => self.receipt(),
+        };
+
+        Container::new(content)
+            .center_x()
+            .center_y()
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .into()
+    }
+
+    fn idle(&self) -> Element<Message> {
+        let logo = match self.settings.logo() {
+            Some(path) => Handle::from_path(path),
+            None => Handle::from_memory(include_bytes!("../resources/logga.png").as_slice()),
+        };
+        padded_column![
+            Image::new(logo).height(Length::Fixed(300.0)),
+            Column::with_children(
+                self.settings
+                    .header()
+                    .iter()
+                    .map(|line| BIG_TEXT::new(line.clone()).into()),
+            )
+            .align_items(Alignment::Center),
+            Text::new("Välkommen!").size(TOTAL_TEXT),
+        ]
+        .align_items(Alignment::Center)
+        .height(Length::Shrink)
+        .into()
+    }
+
+    fn receipt(&self) -> Element<Message> {
+        column![
+            Scrollable::new(
+                Column::with_children(self.receipt.iter().map(|(item, amount)| {
+                    row![
+                        Text::new(format!("{amount} x {}", item.name()))
+                            .size(LINE_TEXT)
+                            .width(Length::Fill),
+                        Text::new(format!("{} kr", item.price() * amount)).size(LINE_TEXT),
+                    ]
+                    .into()
+                }))
+                .spacing(DEF_PADDING)
+                .padding(DEF_PADDING),
+            )
+            .height(Length::Fill),
+            Rule::horizontal(DEF_PADDING),
+            row![
+                Text::new("Totalt").size(TOTAL_TEXT),
+                Space::with_width(Length::Fill),
+                Text::new(format!("{} kr", self.receipt.sum())).size(TOTAL_TEXT),
+            ]
+            .padding(DEF_PADDING),
+        ]
+        .padding(DEF_PADDING)
+        .into()
+    }
+
+    //Same code as on the register so the guest can scan it from here
+    fn swish<'a>(&self, payee: &str, receipt: &Receipt) -> Element<'a, Message> {
+        row![
+            match qr_code(&swish_qr(payee, receipt.sum(), &receipt.reference())) {
+                Some(qr) => Element::from(qr.height(Length::Fill)),
+                None => Element::from(Space::with_width(Length::Shrink)),
+            },
+            padded_column![
+                Text::new("Swish").size(LINE_TEXT),
+                Text::new(format!("{} kr", receipt.sum())).size(TOTAL_TEXT),
+                Text::new(format!("Till {payee}")).size(LINE_TEXT),
+            ]
+            .height(Length::Shrink),
+        ]
+        .align_items(Alignment::Center)
+        .padding(DEF_PADDING)
+        .into()
+    }
+}
@@ -21,6 +21,9 @@ pub enum Icon {
     Money,
     Lock,
     Info,
+    Ticket,
+    Tune,
+    Refresh,
 }
 
 impl From<Icon> for char {
@@ -37,6 +40,9 @@ impl From<Icon> for char {
             Icon::Money => '\u{ef63}',
             Icon::Lock => '\u{e897}',
             Icon::Info => '\u{e88e}',
+            Icon::Ticket => '\u{e638}',
+            Icon::Tune => '\u{e429}',
+            Icon::Refresh => '\u{e5d5}',
         }
     }
 }
@@ -1,4 +1,5 @@
 use {
+    super::images,
     crate::{
         theme::{Container, DEF_PADDING, RECEIPT_WIDTH, SMALL_PADDING},
         widgets::{column, row, SMALL_TEXT},
@@ -6,7 +7,7 @@ use {
     backend::items::{Category, Item as RawItem},
     iced::{
         alignment::Horizontal,
-        widget::{Button, Checkbox, Component, Text},
+        widget::{image::Image, tooltip::Position, Button, Checkbox, Component, Text, Tooltip},
         Color, Element, Length,
     },
 };
@@ -17,6 +18,7 @@ pub struct Item<'a, M> {
     on_press: Option<M>,
     on_toggle: Option<Box<dyn Fn(bool) -> M + 'a>>,
     width: Length,
+    details: bool,
 }
 
 impl<'a, M> Item<'a, M> {
@@ -27,6 +29,7 @@ impl<'a, M> Item<'a, M> {
             on_press: None,
             on_toggle: None,
             width: Length::Fixed(RECEIPT_WIDTH),
+            details: false,
         }
     }
 
@@ -47,6 +50,39 @@ impl<'a, M> Item<'a, M> {
         self.width = width;
         self
     }
+
+    //Show description, allergens and image when hovered
+    pub fn details(mut self, details: bool) -> Self {
+        self.details = details;
+        self
+    }
+
+    fn details_view(&self) -> Option<Element<'_, Event>> {
+        let item = &self.item;
+        if item.description().is_none() && item.allergens().is_empty() && item.image().is_none() {
+            return None;
+        }
+
+        Some(
+            column![
+                match item.image().and_then(images::handle) {
+                    Some(handle) =>
+                        Element::from(Image::new(handle).width(Length::Fixed(RECEIPT_WIDTH))),
+                    None => Element::new(column![]),
+                },
+                Text::new(item.description().clone().unwrap_or_default()),
+                SMALL_TEXT::new(if item.allergens().is_empty() {
+                    String::from("Inga allergener")
+                } else {
+                    format!("Allergener: {}", item.allergens())
+                }),
+            ]
+            .width(Length::Fixed(RECEIPT_WIDTH))
+            .height(Length::Shrink)
+            .spacing(SMALL_PADDING)
+            .into(),
+        )
+    }
 }
 
 impl<M> From<RawItem> for Item<'_, M> {
@@ -57,6 +93,7 @@ impl<M> From<RawItem> for Item<'_, M> {
             on_press: None,
             on_toggle: None,
             width: Length::Fixed(RECEIPT_WIDTH),
+            details: false,
         }
     }
 }
@@ -82,13 +119,24 @@ where
     }
 
     fn view(&self, _state: &Self::State) -> Element<Self::Event> {
-        Button::new(
+        let button = Button::new(
             column![
-                Text::new(self.item.name()),
+                row![
+                    Text::new(self.item.name()).width(Length::Fill),
+                    SMALL_TEXT::new(
+                        self.item
+                            .hotkey()
+                            .map(|c| c.to_uppercase().to_string())
+                            .unwrap_or_default()
+                    ),
+                ],
                 match self.amount {
-                    None | Some(0) => row![SMALL_TEXT::new(format!("{} kr", self.item.price()))
-                        .width(Length::Fill)
-                        .horizontal_alignment(Horizontal::Left)],
+                    None | Some(0) => row![SMALL_TEXT::new(match self.item.member_price() {
+                        Some(member) => format!("{} kr ({member} kr medlem)", self.item.price()),
+                        None => format!("{} kr", self.item.price()),
+                    })
+                    .width(Length::Fill)
+                    .horizontal_alignment(Horizontal::Left)],
                     Some(num) if self.item.is_special() =>
                         row![SMALL_TEXT::new(format!("{} kr", num * self.item.price()))
                             .width(Length::Fill)
@@ -115,18 +163,30 @@ where
         .padding(DEF_PADDING)
         .width(self.width)
         .style(if self.on_press.is_some() {
-            Container::BorderFill(match self.item.category() {
-                Category::Alcohol => Color::from_rgb8(0xFF, 0x6F, 0x59),
-                Category::Drink => Color::from_rgb8(0xC0, 0xDA, 0x74),
-                Category::Food => Color::from_rgb8(0xA7, 0xC6, 0xDA),
-                Category::Other => Color::WHITE,
-            })
+            Container::BorderFill(category_color(*self.item.category()))
         } else {
             Container::Border
         })
         .clip(true)
-        .on_press(Event::Press)
-        .into()
+        .on_press(Event::Press);
+
+        match self.details.then(|| self.details_view()).flatten() {
+            Some(details) => Tooltip::new(button, details, Position::Bottom)
+                .style(Container::BorderFill(Color::WHITE))
+                .padding(DEF_PADDING)
+                .into(),
+            None => button.into(),
+        }
+    }
+}
+
+pub fn category_color(category: Category) -> Color {
+    match category {
+        Category::Alcohol => Color::from_rgb8(0xFF, 0x6F, 0x59),
+        Category::Drink => Color::from_rgb8(0xC0, 0xDA, 0x74),
+        Category::Food => Color::from_rgb8(0xA7, 0xC6, 0xDA),
+        Category::Ticket => Color::from_rgb8(0xF4, 0xD3, 0x5E),
+        Category::Other => Color::WHITE,
     }
 }
 
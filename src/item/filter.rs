@@ -0,0 +1,79 @@
+use {
+    super::component::category_color,
+    crate::{
+        theme::{Container, DEF_PADDING},
+        widgets::{column, SMALL_TEXT},
+    },
+    backend::items::{Category, Item},
+    iced::{
+        widget::{Button, Row, TextInput},
+        Element, Length,
+    },
+    strum::VariantArray,
+};
+
+#[derive(Debug, Clone, Default)]
+pub struct Filter {
+    search: String,
+    //Empty means all categories
+    categories: Vec<Category>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Event {
+    Search(String),
+    ToggleCategory(Category),
+}
+
+impl Filter {
+    pub fn update(&mut self, event: Event) {
+        match event {
+            Event::Search(s) => self.search = s,
+            Event::ToggleCategory(c) => match self.categories.iter().position(|v| *v == c) {
+                Some(i) => {
+                    self.categories.remove(i);
+                }
+                None => self.categories.push(c),
+            },
+        }
+    }
+
+    pub fn matches(&self, item: &Item) -> bool {
+        (self.categories.is_empty() || self.categories.contains(item.category()))
+            && fuzzy_match(&self.search, item.name())
+    }
+
+    pub fn view<'a>(&self) -> Element<'a, Event> {
+        column![
+            TextInput::new("Sök", &self.search)
+                .on_input(Event::Search)
+                .padding(DEF_PADDING),
+            Row::with_children(Category::VARIANTS.iter().map(|c| {
+                Button::new(SMALL_TEXT::new(c.to_string()))
+                    .on_press(Event::ToggleCategory(*c))
+                    .padding(DEF_PADDING)
+                    .style(if self.categories.contains(c) {
+                        Container::BorderFill(category_color(*c))
+                    } else {
+                        Container::Border
+                    })
+                    .into()
+            }))
+            .spacing(DEF_PADDING),
+        ]
+        .height(Length::Shrink)
+        .spacing(DEF_PADDING)
+        .padding(DEF_PADDING)
+        .into()
+    }
+}
+
+//All characters of the query appear in order in the name, ignoring case
+fn fuzzy_match(query: &str, name: &str) -> bool {
+    let mut name = name.chars().flat_map(char::to_lowercase);
+    query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .all(|q| name.any(|n| n == q))
+}
@@ -0,0 +1,37 @@
+use {
+    backend::items::{images, Item},
+    iced::widget::image::Handle,
+    std::{
+        collections::{HashMap, HashSet},
+        sync::{LazyLock, Mutex},
+    },
+};
+
+//Pictures read so far by id, an item gets a new id when its picture is changed so they never go
+//stale. Made into handles once so they are not decoded again every frame
+static HANDLES: LazyLock<Mutex<HashMap<i64, Handle>>> = LazyLock::new(Default::default);
+
+pub fn handle(id: i64) -> Option<Handle> {
+    HANDLES
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(&id)
+        .cloned()
+}
+
+//Reads the pictures not read before, an item whose picture can not be read is shown without it
+pub async fn load<'a>(items: impl IntoIterator<Item = &'a Item>) {
+    let missing: HashSet<i64> = items
+        .into_iter()
+        .filter_map(|item| *item.image())
+        .filter(|id| handle(*id).is_none())
+        .collect();
+    for id in missing {
+        if let Ok(Some(bytes)) = images::get(id).await {
+            HANDLES
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .insert(id, Handle::from_memory(bytes));
+        }
+    }
+}
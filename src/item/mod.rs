@@ -1 +1,3 @@
 pub mod component;
+pub mod filter;
+pub mod images;
@@ -0,0 +1,172 @@
+use {
+    crate::{
+        config,
+        error::Result,
+        theme::{self, DEF_PADDING},
+        widgets::{column, padded_column, row, BIG_TEXT, SMALL_TEXT},
+    },
+    backend::orders::{Order, OrderStatus},
+    chrono::{DateTime, Local},
+    iced::{
+        font,
+        widget::{Button, Column, Container, Scrollable, Space, Text},
+        Application, Color, Command, Element, Length, Subscription,
+    },
+    iced_aw::Wrap,
+    std::time::Duration,
+};
+
+//New orders show up within this time, there is no notification between processes
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+//Orders waiting longer than this are marked
+const LATE_MINUTES: i64 = 15;
+const CARD_WIDTH: f32 = 400.0;
+
+//Kitchen display, started with --kds on a screen in the kitchen and sharing the database or the
+//register server
+pub struct Kitchen {
+    orders: Vec<Order>,
+    //When the orders were loaded, ages are counted from here
+    now: DateTime<Local>,
+    error: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    None,
+    Load,
+    Loaded(Result<Vec<Order>>),
+    SetStatus(Order, OrderStatus),
+}
+
+impl From<std::result::Result<(), font::Error>> for Message {
+    fn from(_: std::result::Result<(), font::Error>) -> Self {
+        Self::None
+    }
+}
+
+async fn load() -> Result<Vec<Order>> {
+    Ok(Order::get_open().await?)
+}
+
+impl Application for Kitchen {
+    type Executor = iced::executor::Default;
+    type Message = Message;
+    type Flags = ();
+    type Theme = iced::Theme;
+
+    fn new(_: Self::Flags) -> (Self, Command<Self::Message>) {
+        (
+            Self {
+                orders: Vec::new(),
+                now: Local::now(),
+                error: None,
+            },
+            Command::batch([
+                font::load(include_bytes!("../resources/IBMPlexMono-Regular.ttf").as_slice())
+                    .map(Message::from),
+                Command::perform(
+                    async {
+                        config::init_db()?;
+                        config::connect_client().await?;
+                        load().await
+                    },
+                    Message::Loaded,
+                ),
+            ]),
+        )
+    }
+
+    fn title(&self) -> String {
+        String::from("Kök")
+    }
+
+    fn update(&mut self, msg: Self::Message) -> Command<Self::Message> {
+        match msg {
+            Message::None => (),
+            Message::Load => return Command::perform(load(), Message::Loaded),
+            Message::Loaded(Ok(orders)) => {
+                self.orders = orders;
+                self.now = Local::now();
+                self.error = None;
+            }
+            Message::Loaded(Err(e)) => self.error = Some(format!("{e:?}")),
+            Message::SetStatus(order, status) => {
+                return Command::perform(
+                    async move {
+                        order.set_status(status).await?;
+                        load().await
+                    },
+                    Message::Loaded,
+                );
+            }
+        }
+        Command::none()
+    }
+
+    fn subscription(&self) -> Subscription<Self::Message> {
+        iced::time::every(POLL_INTERVAL).map(|_| Message::Load)
+    }
+
+    fn view(&self) -> Element<Self::Message> {
+        column![
+            match &self.error {
+                Some(error) => Element::from(SMALL_TEXT::new(error.clone())),
+                None if self.orders.is_empty() => Text::new("Inga öppna ordrar").into(),
+                None => Space::with_height(Length::Shrink).into(),
+            },
+            Scrollable::new(
+                Wrap::with_elements(self.orders.iter().map(|order| self.card(order)).collect())
+                    .spacing(DEF_PADDING as f32)
+                    .line_spacing(DEF_PADDING as f32)
+                    .padding(DEF_PADDING as f32),
+            )
+            .height(Length::Fill),
+        ]
+        .into()
+    }
+}
+
+impl Kitchen {
+    fn card(&self, order: &Order) -> Element<Message> {
+        let age = (self.now - *order.receipt()).num_minutes().max(0);
+        let next = match order.status() {
+            OrderStatus::Open => (OrderStatus::InProgress, "Påbörja"),
+            _ => (OrderStatus::Done, "Klar"),
+        };
+
+        Container::new(
+            padded_column![
+                row![
+                    BIG_TEXT::new(format!("Order {}", order.number())),
+                    Space::with_width(Length::Fill),
+                    Text::new(format!("{age} min")),
+                ],
+                SMALL_TEXT::new(format!(
+                    "{} {}",
+                    order.receipt().format("%R"),
+                    order.status()
+                )),
+                Column::with_children(
+                    order
+                        .items()
+                        .iter()
+                        .map(|(item, amount)| Text::new(format!("{amount} x {item}")).into()),
+                ),
+                Button::new(BIG_TEXT::new(next.1))
+                    .on_press(Message::SetStatus(order.clone(), next.0))
+                    .padding(DEF_PADDING)
+                    .width(Length::Fill)
+                    .style(theme::Container::Border),
+            ]
+            .height(Length::Shrink),
+        )
+        .width(Length::Fixed(CARD_WIDTH))
+        .style(if age >= LATE_MINUTES {
+            theme::Container::BorderFill(Color::from_rgb8(0xFF, 0xB3, 0xA7))
+        } else {
+            theme::Container::Border
+        })
+        .into()
+    }
+}
@@ -1,23 +1,28 @@
 use {
     crate::{
+        customer::Display,
         icons::Icon,
-        screens::{Message, Tab, TabId},
-        theme::{DEF_PADDING, DEF_TEXT, TabStyle},
+        screens::{menu::PendingPayment, Message, Tab, TabId},
+        theme::{TabStyle, DEF_PADDING, DEF_TEXT},
         widgets::SMALL_TEXT,
     },
+    backend::terminal::PaymentState,
     chrono::Local,
     iced::{
-        Application, Command, Element, Font, Length, Pixels, Settings, Size, font,
+        event, font,
+        multi_window::Application,
         widget::{Container, Text},
-        window,
+        window, Command, Element, Event, Font, Length, Pixels, Settings, Size, Subscription,
     },
     iced_aw::{Card, Modal, TabLabel, Tabs},
 };
 
 pub mod config;
+pub mod customer;
 pub mod error;
 pub mod icons;
 pub mod item;
+pub mod kitchen;
 pub mod receipt;
 pub mod screens;
 pub mod theme;
@@ -33,7 +38,15 @@ macro_rules! command {
 pub const FONT: Font = Font::with_name("IBM Plex Mono");
 
 pub fn main() -> iced::Result {
-    App::run(Settings {
+    //Kitchen display instead of the register
+    if std::env::args().any(|arg| arg == "--kds") {
+        return <kitchen::Kitchen as iced::Application>::run(settings());
+    }
+    App::run(settings())
+}
+
+fn settings() -> Settings<()> {
+    Settings {
         window: window::Settings {
             min_size: Some(Size {
                 width: 1360.0,
@@ -44,12 +57,16 @@ pub fn main() -> iced::Result {
         default_font: FONT,
         default_text_size: Pixels(DEF_TEXT),
         ..Settings::default()
-    })
+    }
 }
 
 struct App {
     modal: Option<(&'static str, String)>,
     tab: Tab,
+    //Open card payment, shown on the menu until it is settled
+    payment: Option<PendingPayment>,
+    //Customer window, opened when it is turned on in the settings
+    display: Option<(window::Id, Display)>,
 }
 
 impl Application for App {
@@ -62,23 +79,31 @@ impl Application for App {
         (
             Self {
                 modal: None,
-                tab: Tab::Menu(Vec::new()),
+                tab: Tab::Menu(Default::default()),
+                payment: None,
+                display: None,
             },
             Command::batch([
-                command!(config::init_db()),
+                command!({
+                    config::init_db()?;
+                    config::connect_server().await?;
+                    TabId::Menu.load().await
+                }),
                 command!(config::set_receipt_path()),
                 font::load(include_bytes!("../resources/IBMPlexMono-Regular.ttf").as_slice())
                     .map(Message::from),
                 font::load(include_bytes!("../resources/google-fonts-icons.ttf").as_slice())
                     .map(Message::from),
                 font::load(iced_aw::core::icons::BOOTSTRAP_FONT_BYTES).map(Message::from),
-                command!(TabId::Menu.load().await),
             ]),
         )
     }
 
-    fn title(&self) -> String {
-        String::from("Kassa")
+    fn title(&self, window: window::Id) -> String {
+        match &self.display {
+            Some((id, _)) if *id == window => String::from("Kund"),
+            _ => String::from("Kassa"),
+        }
     }
 
     fn update(&mut self, msg: Self::Message) -> Command<Self::Message> {
@@ -86,8 +111,22 @@ impl Application for App {
             Message::None => Command::none(),
             Message::SwapTab(tab) => command!(tab.load().await),
             Message::LoadTab(tab) => {
+                let command = match (&tab, &self.display) {
+                    (Tab::Menu(data), None) if *data.settings.customer_display() => {
+                        let (id, command) = window::spawn(window::Settings::default());
+                        let idle = Display::new(Default::default(), None, data.settings.clone());
+                        self.display = Some((id, idle));
+                        command
+                    }
+                    (Tab::Menu(data), Some((id, _))) if !data.settings.customer_display() => {
+                        let command = window::close(*id);
+                        self.display = None;
+                        command
+                    }
+                    _ => Command::none(),
+                };
                 self.tab = tab;
-                Command::none()
+                command
             }
             Message::CloseModal => {
                 self.modal = None;
@@ -104,10 +143,93 @@ impl Application for App {
             Message::Sideffect(f) => command! {
                 Message::from(f.await)
             },
+            Message::RequestPayment(payment) => {
+                if self
+                    .payment
+                    .as_ref()
+                    .is_some_and(PendingPayment::is_pending)
+                {
+                    return Command::none();
+                }
+                self.payment = Some(payment.clone());
+                if payment.is_pending() {
+                    command!(payment.poll().await)
+                } else {
+                    Command::none()
+                }
+            }
+            //Answers for a payment that was already settled can arrive late, e.g. a poll
+            //finishing after the payment was cancelled
+            Message::UpdatePayment(payment) => {
+                if !self
+                    .payment
+                    .as_ref()
+                    .is_some_and(|p| p.id() == payment.id() && p.is_pending())
+                {
+                    return Command::none();
+                }
+                match payment.state() {
+                    PaymentState::Pending => {
+                        self.payment = Some(payment.clone());
+                        command!(payment.poll().await)
+                    }
+                    PaymentState::Confirmed => {
+                        self.payment = None;
+                        command!(payment.sell())
+                    }
+                    _ => {
+                        self.payment = Some(payment);
+                        Command::none()
+                    }
+                }
+            }
+            Message::ClosePayment => {
+                self.payment = None;
+                Command::none()
+            }
+            Message::UpdateDisplay(display) => {
+                if let Some((_, old)) = &mut self.display {
+                    *old = *display;
+                }
+                Command::none()
+            }
+            //The register keeps running without the customer window, but not the other way around
+            Message::WindowClosed(id) if id == window::Id::MAIN => match self.display.take() {
+                Some((display, _)) => window::close(display),
+                None => Command::none(),
+            },
+            Message::WindowClosed(id) => {
+                if self
+                    .display
+                    .as_ref()
+                    .is_some_and(|(display, _)| *display == id)
+                {
+                    self.display = None;
+                }
+                Command::none()
+            }
+            Message::Batch(messages) => {
+                let commands = messages
+                    .into_iter()
+                    .map(|msg| self.update(msg))
+                    .collect::<Vec<_>>();
+                Command::batch(commands)
+            }
         }
     }
 
-    fn view(&self) -> Element<Self::Message> {
+    fn subscription(&self) -> Subscription<Self::Message> {
+        event::listen_with(|event, _| match event {
+            Event::Window(id, window::Event::Closed) => Some(Message::WindowClosed(id)),
+            _ => None,
+        })
+    }
+
+    fn view(&self, window: window::Id) -> Element<Self::Message> {
+        if let Some((_, display)) = self.display.as_ref().filter(|(id, _)| *id == window) {
+            return display.view(self.payment.as_ref());
+        }
+
         Modal::new(
             Container::new(
                 Tabs::new(Message::SwapTab)
@@ -119,7 +241,7 @@ impl Application for App {
                     .push(
                         TabId::Menu,
                         TabLabel::IconText(Icon::Menu.into(), String::from("Meny")),
-                        self.tab.as_menu(),
+                        self.tab.as_menu(self.payment.as_ref()),
                     )
                     .push(
                         TabId::Transactions,
@@ -130,15 +252,26 @@ impl Application for App {
                         TabId::Sales {
                             from: Local::now().date_naive(),
                             to: Local::now().date_naive(),
+                            event: None,
                         },
                         TabLabel::IconText(Icon::Money.into(), String::from("Försäljning")),
                         self.tab.as_sales(),
                     )
+                    .push(
+                        TabId::Tickets,
+                        TabLabel::IconText(Icon::Ticket.into(), String::from("Biljetter")),
+                        self.tab.as_tickets(),
+                    )
                     .push(
                         TabId::Manager,
                         TabLabel::IconText(Icon::Settings.into(), String::from("Hantera")),
                         self.tab.as_manager(),
                     )
+                    .push(
+                        TabId::Settings,
+                        TabLabel::IconText(Icon::Tune.into(), String::from("Inställningar")),
+                        self.tab.as_settings(),
+                    )
                     .push(
                         TabId::Info,
                         TabLabel::IconText(Icon::Info.into(), String::from("Systeminfo")),
@@ -2,13 +2,30 @@ use {
     super::{Message, Sideffect, TabId},
     crate::{
         icons::Icon,
+        item::{
+            filter::{self, Filter},
+            images,
+        },
         theme::{self, DEF_PADDING, RECEIPT_WIDTH},
-        widgets::{padded_column, row, NumberInput, SquareButton, BIG_TEXT},
+        widgets::{column, padded_column, row, NumberInput, SquareButton, BIG_TEXT, SMALL_TEXT},
+    },
+    backend::{
+        accounts::Account,
+        events,
+        items::{
+            category::Category,
+            transfer::{self, Format, Import},
+            Alcohol, AlcoholKind, Allergen, Allergens, Item,
+        },
+        members::Member,
+        vouchers::Voucher,
     },
-    backend::items::{category::Category, Item},
+    chrono::{Local, NaiveDate, NaiveDateTime},
     iced::{
         widget::{
-            Button, Component, PickList, Responsive, Rule, Scrollable, Space, Text, TextInput,
+            image::{Handle, Image},
+            Button, Checkbox, Column, Component, PickList, Responsive, Rule, Scrollable, Space,
+            Text, TextInput,
         },
         Alignment, Element, Length, Size,
     },
@@ -18,6 +35,10 @@ use {
 
 pub struct Manager {
     menu: Vec<Item>,
+    //Names of the items pinned on this register
+    pinned: Vec<String>,
+    age_checked: Vec<Category>,
+    events: Vec<events::Event>,
 }
 
 #[derive(Debug, Clone, Default)]
@@ -34,7 +55,45 @@ pub struct State {
     mode: Mode,
     name: String,
     price: i32,
+    //0 means same as price
+    member_price: i32,
     category: Option<Category>,
+    barcode: String,
+    hotkey: Option<char>,
+    favorite: bool,
+    archived: bool,
+    description: String,
+    allergens: Allergens,
+    //Shown in the form, the picture itself is only sent when it was changed
+    image: Option<Handle>,
+    new_image: Option<Option<Vec<u8>>>,
+    image_path: String,
+    alcohol_kind: Option<AlcoholKind>,
+    volume: i32,
+    //Percentage, stored as per mille
+    abv: f32,
+    details_modal: bool,
+    delete_modal: bool,
+    categories_modal: bool,
+    events_modal: bool,
+    event_name: String,
+    event_start: String,
+    event_end: String,
+    members_modal: bool,
+    member_number: String,
+    member_name: String,
+    member_valid: String,
+    accounts_modal: bool,
+    account_card: String,
+    account_holder: String,
+    vouchers_modal: bool,
+    voucher_value: i32,
+    voucher_expires: String,
+    show_archived: bool,
+    filter: Filter,
+    transfer_modal: bool,
+    transfer_path: String,
+    preview: Option<Import>,
 }
 
 impl Default for State {
@@ -46,29 +105,147 @@ impl Default for State {
             mode: Mode::New,
             name: String::new(),
             price: 0,
+            member_price: 0,
             category: None,
+            barcode: String::new(),
+            hotkey: None,
+            favorite: false,
+            archived: false,
+            description: String::new(),
+            allergens: Allergens::default(),
+            image: None,
+            new_image: None,
+            image_path: String::new(),
+            alcohol_kind: None,
+            volume: 0,
+            abv: 0.0,
+            details_modal: false,
+            delete_modal: false,
+            categories_modal: false,
+            events_modal: false,
+            event_name: String::new(),
+            event_start: String::new(),
+            event_end: String::new(),
+            members_modal: false,
+            member_number: String::new(),
+            member_name: String::new(),
+            member_valid: String::new(),
+            accounts_modal: false,
+            account_card: String::new(),
+            account_holder: String::new(),
+            vouchers_modal: false,
+            voucher_value: 100,
+            voucher_expires: String::new(),
+            show_archived: false,
+            filter: Filter::default(),
+            transfer_modal: false,
+            transfer_path: String::new(),
+            preview: None,
         }
     }
 }
 
+impl State {
+    fn clear(&mut self) {
+        self.mode = Mode::New;
+        self.name.clear();
+        self.price = 0;
+        self.member_price = 0;
+        self.barcode.clear();
+        self.hotkey = None;
+        self.favorite = false;
+        self.archived = false;
+        self.description.clear();
+        self.allergens = Allergens::default();
+        self.image = None;
+        self.new_image = None;
+        self.image_path.clear();
+        self.alcohol_kind = None;
+        self.volume = 0;
+        self.abv = 0.0;
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum Event {
     ToggleItem(usize, bool),
     EditItem(usize),
     UpdateName(String),
     UpdatePrice(i32),
+    UpdateMemberPrice(i32),
     UpdateCategory(Category),
+    UpdateBarcode(String),
+    UpdateHotkey(String),
+    UpdateFavorite(bool),
+    UpdateAlcoholKind(AlcoholKind),
+    UpdateVolume(i32),
+    UpdateAbv(f32),
+    OpenDetails,
+    CloseDetails,
+    UpdateDescription(String),
+    ToggleAllergen(Allergen, bool),
+    UpdateImagePath(String),
+    LoadImage,
+    RemoveImage,
     Cancel,
     Save,
+    Archive,
+    Delete,
+    CloseDelete,
+    ConfirmDelete,
+    ShowArchived(bool),
+    OpenCategories,
+    CloseCategories,
+    ToggleAgeCheck(Category, bool),
+    OpenEvents,
+    CloseEvents,
+    UpdateEventName(String),
+    UpdateEventStart(String),
+    UpdateEventEnd(String),
+    CreateEvent,
+    ActivateEvent(usize, bool),
+    OpenMembers,
+    CloseMembers,
+    UpdateMemberNumber(String),
+    UpdateMemberName(String),
+    UpdateMemberValid(String),
+    SaveMember,
+    OpenAccounts,
+    CloseAccounts,
+    UpdateAccountCard(String),
+    UpdateAccountHolder(String),
+    SaveAccount,
+    OpenVouchers,
+    CloseVouchers,
+    UpdateVoucherValue(i32),
+    UpdateVoucherExpires(String),
+    IssueVoucher,
+    OpenTransfer,
+    CloseTransfer,
+    UpdateTransferPath(String),
+    Export(Format),
+    Preview,
+    Import,
     OpenLogin,
     CloseLogin,
     UpdatePassword(String),
     Login,
+    Filter(filter::Event),
 }
 
 impl Manager {
-    pub fn new(menu: Vec<Item>) -> Self {
-        Self { menu }
+    pub fn new(
+        menu: Vec<Item>,
+        pinned: Vec<String>,
+        age_checked: Vec<Category>,
+        events: Vec<events::Event>,
+    ) -> Self {
+        Self {
+            menu,
+            pinned,
+            age_checked,
+            events,
+        }
     }
 }
 
@@ -97,28 +274,101 @@ impl Component<Message> for Manager {
                 state.mode = Mode::Update(item.name().clone());
                 state.name = item.name().clone();
                 state.price = *item.price();
+                state.member_price = item.member_price().unwrap_or_default();
                 state.category = Some(*item.category());
+                state.barcode = item.barcode().clone().unwrap_or_default();
+                state.hotkey = *item.hotkey();
+                state.favorite = self.pinned.contains(item.name());
+                state.archived = item.is_archived();
+                state.description = item.description().clone().unwrap_or_default();
+                state.allergens = *item.allergens();
+                state.image = item.image().and_then(images::handle);
+                state.new_image = None;
+                state.image_path.clear();
+                state.alcohol_kind = item.alcohol().map(|a| a.kind);
+                state.volume = item.alcohol().map(|a| a.volume).unwrap_or_default();
+                state.abv = item
+                    .alcohol()
+                    .map(|a| a.percentage() as f32)
+                    .unwrap_or_default();
             }
             Event::UpdateName(s) => state.name = s,
             Event::UpdatePrice(n) => state.price = n,
+            Event::UpdateMemberPrice(n) => state.member_price = n,
             Event::UpdateCategory(c) => state.category = Some(c),
-            Event::Cancel => {
-                state.mode = Mode::New;
-                state.name.clear();
-                state.price = 0;
+            Event::UpdateBarcode(s) => state.barcode = s,
+            //Only letters since digits are used for the multiplier and barcodes
+            Event::UpdateHotkey(s) => {
+                state.hotkey = s
+                    .chars()
+                    .rev()
+                    .find(|c| c.is_alphabetic())
+                    .and_then(|c| c.to_lowercase().next())
+            }
+            Event::UpdateFavorite(b) => state.favorite = b,
+            Event::UpdateAlcoholKind(k) => state.alcohol_kind = Some(k),
+            Event::UpdateVolume(n) => state.volume = n,
+            Event::UpdateAbv(n) => state.abv = n,
+            Event::OpenDetails => state.details_modal = true,
+            Event::CloseDetails => state.details_modal = false,
+            Event::UpdateDescription(s) => state.description = s,
+            Event::ToggleAllergen(a, b) => state.allergens = state.allergens.with(a, b),
+            Event::UpdateImagePath(s) => state.image_path = s,
+            Event::LoadImage => match std::fs::read(&state.image_path) {
+                Ok(bytes) => {
+                    if let Err(e) = backend::items::images::check(&bytes) {
+                        return Some(Err::<Message, _>(e).into());
+                    }
+                    state.image = Some(Handle::from_memory(bytes.clone()));
+                    state.new_image = Some(Some(bytes));
+                }
+                Err(e) => return Some(Err::<Message, _>(e.kind()).into()),
+            },
+            Event::RemoveImage => {
+                state.image = None;
+                state.new_image = Some(None);
             }
+            Event::Cancel => state.clear(),
             Event::Save => {
                 use std::mem::take;
                 let name = take(&mut state.name);
                 if !name.is_empty() {
+                    let barcode = take(&mut state.barcode);
+                    let description = take(&mut state.description);
+                    let category = take(&mut state.category).unwrap_or_default();
+                    //Only alcohol is reported to the licensing authority
+                    let alcohol = take(&mut state.alcohol_kind)
+                        .filter(|_| category == Category::Alcohol)
+                        .map(|kind| Alcohol {
+                            kind,
+                            volume: take(&mut state.volume),
+                            abv: (take(&mut state.abv) * 10.0).round() as i32,
+                        });
                     let item = Item::new()
                         .with_name(name)
                         .with_price(take(&mut state.price))
-                        .with_category(take(&mut state.category).unwrap_or_default());
+                        .with_member_price(Some(take(&mut state.member_price)).filter(|p| *p > 0))
+                        .with_category(category)
+                        .with_barcode((!barcode.is_empty()).then_some(barcode))
+                        .with_hotkey(take(&mut state.hotkey))
+                        .with_description((!description.is_empty()).then_some(description))
+                        .with_allergens(take(&mut state.allergens))
+                        .with_alcohol(alcohol);
+                    //Pinned on this register only and the picture only when changed, both
+                    //stored apart from the item
+                    let favorite = take(&mut state.favorite);
+                    let image = take(&mut state.new_image);
+                    let saved = Item::new().with_name(item.name().clone());
+                    state.image = None;
+                    state.image_path.clear();
                     return match std::mem::take(&mut state.mode) {
                         Mode::New => Some(
                             Sideffect::new(|| async move {
                                 item.insert_new().await?;
+                                saved.clone().change_favorite(favorite).await?;
+                                if let Some(image) = image {
+                                    saved.change_image(image).await?;
+                                }
 
                                 //TODO see change_availability
                                 TabId::Manager.load().await
@@ -130,6 +380,10 @@ impl Component<Message> for Manager {
                             Some(
                                 Sideffect::new(|| async move {
                                     old.update(item).await?;
+                                    saved.clone().change_favorite(favorite).await?;
+                                    if let Some(image) = image {
+                                        saved.change_image(image).await?;
+                                    }
 
                                     //TODO see change_availability
                                     TabId::Manager.load().await
@@ -140,6 +394,267 @@ impl Component<Message> for Manager {
                     };
                 }
             }
+            Event::Archive => {
+                if let Mode::Update(name) = std::mem::take(&mut state.mode) {
+                    let archived = !state.archived;
+                    state.clear();
+                    return Some(
+                        Sideffect::new(|| async move {
+                            Item::new()
+                                .with_name(name)
+                                .change_archived(archived)
+                                .await?;
+                            TabId::Manager.load().await
+                        })
+                        .into(),
+                    );
+                }
+            }
+            Event::Delete => state.delete_modal = true,
+            Event::CloseDelete => state.delete_modal = false,
+            Event::ConfirmDelete => {
+                state.delete_modal = false;
+                if let Mode::Update(name) = std::mem::take(&mut state.mode) {
+                    state.clear();
+                    return Some(
+                        Sideffect::new(|| async move {
+                            match Item::new().with_name(name).delete().await {
+                                Err(backend::Error::ItemInUse) => {
+                                    return Ok(Message::OpenModal {
+                                        title: "Kan inte raderas",
+                                        content: String::from(
+                                            "Produkten har sålts och kan bara arkiveras",
+                                        ),
+                                    })
+                                }
+                                Err(backend::Error::SpecialItem) => {
+                                    return Ok(Message::OpenModal {
+                                        title: "Kan inte raderas",
+                                        content: String::from(
+                                            "Specialprodukter används av kassan och kan inte \
+                                             raderas",
+                                        ),
+                                    })
+                                }
+                                result => result?,
+                            }
+                            TabId::Manager.load().await
+                        })
+                        .into(),
+                    );
+                }
+            }
+            Event::ShowArchived(b) => state.show_archived = b,
+            Event::OpenCategories => state.categories_modal = true,
+            Event::CloseCategories => state.categories_modal = false,
+            Event::ToggleAgeCheck(category, b) => {
+                return Some(
+                    Sideffect::new(|| async move {
+                        category.change_age_check(b).await?;
+                        TabId::Manager.load().await
+                    })
+                    .into(),
+                );
+            }
+            Event::OpenEvents => state.events_modal = true,
+            Event::CloseEvents => state.events_modal = false,
+            Event::UpdateEventName(s) => state.event_name = s,
+            Event::UpdateEventStart(s) => state.event_start = s,
+            Event::UpdateEventEnd(s) => state.event_end = s,
+            Event::CreateEvent => {
+                let parse = |s: &str| {
+                    NaiveDateTime::parse_from_str(s, "%F %R")
+                        .ok()?
+                        .and_local_timezone(Local)
+                        .single()
+                };
+                return match (parse(&state.event_start), parse(&state.event_end)) {
+                    (Some(start), Some(end)) if !state.event_name.is_empty() && start < end => {
+                        let event = events::Event::new(std::mem::take(&mut state.event_name), start, end);
+                        state.event_start.clear();
+                        state.event_end.clear();
+                        Some(
+                            Sideffect::new(|| async move {
+                                event.insert_new().await?;
+                                TabId::Manager.load().await
+                            })
+                            .into(),
+                        )
+                    }
+                    _ => Some(Message::OpenModal {
+                        title: "Ogiltig händelse",
+                        content: String::from(
+                            "Händelsen behöver ett namn och en start före slutet (ÅÅÅÅ-MM-DD TT:MM)",
+                        ),
+                    }),
+                };
+            }
+            Event::ActivateEvent(i, b) => {
+                if let Some(event) = self.events.get(i) {
+                    let event = event.clone();
+                    return Some(
+                        Sideffect::new(|| async move {
+                            event.change_active(b).await?;
+                            TabId::Manager.load().await
+                        })
+                        .into(),
+                    );
+                }
+            }
+            Event::OpenMembers => state.members_modal = true,
+            Event::CloseMembers => state.members_modal = false,
+            Event::UpdateMemberNumber(s) => state.member_number = s,
+            Event::UpdateMemberName(s) => state.member_name = s,
+            Event::UpdateMemberValid(s) => state.member_valid = s,
+            Event::SaveMember => {
+                return match NaiveDate::parse_from_str(&state.member_valid, "%F") {
+                    Ok(valid_until)
+                        if !state.member_number.is_empty() && !state.member_name.is_empty() =>
+                    {
+                        let member = Member::new(
+                            std::mem::take(&mut state.member_number),
+                            std::mem::take(&mut state.member_name),
+                            valid_until,
+                        );
+                        state.member_valid.clear();
+                        Some(
+                            Sideffect::new(|| async move {
+                                let content = format!(
+                                    "{} är medlem t.o.m. {}",
+                                    member.name(),
+                                    member.valid_until().format("%F")
+                                );
+                                member.insert_or_update().await?;
+                                Ok(Message::OpenModal {
+                                    title: "Sparad",
+                                    content,
+                                })
+                            })
+                            .into(),
+                        )
+                    }
+                    _ => Some(Message::OpenModal {
+                        title: "Ogiltig medlem",
+                        content: String::from(
+                            "Medlemmen behöver nummer, namn och ett giltigt datum (ÅÅÅÅ-MM-DD)",
+                        ),
+                    }),
+                };
+            }
+            Event::OpenAccounts => state.accounts_modal = true,
+            Event::CloseAccounts => state.accounts_modal = false,
+            Event::UpdateAccountCard(s) => state.account_card = s,
+            Event::UpdateAccountHolder(s) => state.account_holder = s,
+            Event::SaveAccount => {
+                if state.account_card.is_empty() || state.account_holder.is_empty() {
+                    return Some(Message::OpenModal {
+                        title: "Ogiltigt saldokort",
+                        content: String::from("Saldokortet behöver kortnummer och innehavare"),
+                    });
+                }
+                let account = Account::new(
+                    std::mem::take(&mut state.account_card),
+                    std::mem::take(&mut state.account_holder),
+                );
+                return Some(
+                    Sideffect::new(|| async move {
+                        let content = format!(
+                            "Saldokort {} skapat för {}",
+                            account.card(),
+                            account.holder()
+                        );
+                        account.insert_new().await?;
+                        Ok(Message::OpenModal {
+                            title: "Sparad",
+                            content,
+                        })
+                    })
+                    .into(),
+                );
+            }
+            Event::OpenVouchers => {
+                //Valid for a year unless something else is entered
+                if state.voucher_expires.is_empty() {
+                    state.voucher_expires = (Local::now().date_naive() + chrono::Months::new(12))
+                        .format("%F")
+                        .to_string();
+                }
+                state.vouchers_modal = true;
+            }
+            Event::CloseVouchers => state.vouchers_modal = false,
+            Event::UpdateVoucherValue(n) => state.voucher_value = n,
+            Event::UpdateVoucherExpires(s) => state.voucher_expires = s,
+            Event::IssueVoucher => {
+                return match NaiveDate::parse_from_str(&state.voucher_expires, "%F") {
+                    Ok(expires) if expires >= Local::now().date_naive() => {
+                        let voucher = Voucher::new(state.voucher_value, expires);
+                        Some(
+                            Sideffect::new(|| async move {
+                                let voucher = voucher.issue().await?;
+                                let printed = match voucher.print().await {
+                                    Ok(()) => String::new(),
+                                    Err(e) => format!("\nKunde inte skrivas ut: {e:?}"),
+                                };
+                                Ok(Message::OpenModal {
+                                    title: "Presentkort skapat",
+                                    content: format!(
+                                        "Kod {} på {} kr, giltigt t.o.m. {}{printed}",
+                                        voucher.code(),
+                                        voucher.value(),
+                                        voucher.expires().format("%F")
+                                    ),
+                                })
+                            })
+                            .into(),
+                        )
+                    }
+                    _ => Some(Message::OpenModal {
+                        title: "Ogiltigt presentkort",
+                        content: String::from(
+                            "Presentkortet behöver ett giltigt datum framåt i tiden (ÅÅÅÅ-MM-DD)",
+                        ),
+                    }),
+                };
+            }
+            Event::OpenTransfer => state.transfer_modal = true,
+            Event::CloseTransfer => {
+                state.transfer_modal = false;
+                state.preview = None;
+            }
+            Event::UpdateTransferPath(s) => {
+                state.transfer_path = s;
+                state.preview = None;
+            }
+            Event::Export(format) => {
+                return Some(
+                    Sideffect::new(|| async move {
+                        let path = transfer::export(format).await?;
+                        Ok(Message::OpenModal {
+                            title: "Sparad",
+                            content: format!("Sparad till {}", path.to_string_lossy()),
+                        })
+                    })
+                    .into(),
+                );
+            }
+            //Read without touching the database, applying the import is a separate step
+            Event::Preview => match transfer::read(&state.transfer_path) {
+                Ok(items) => state.preview = Some(Import::new(&self.menu, items)),
+                Err(e) => return Some(Err::<Message, _>(backend::Error::from(e)).into()),
+            },
+            Event::Import => {
+                if let Some(import) = state.preview.take() {
+                    state.transfer_modal = false;
+                    return Some(
+                        Sideffect::new(|| async move {
+                            import.apply().await?;
+                            TabId::Manager.load().await
+                        })
+                        .into(),
+                    );
+                }
+            }
+            Event::Filter(e) => state.filter.update(e),
             Event::OpenLogin => state.login_modal = true,
             Event::CloseLogin => state.login_modal = false,
             Event::UpdatePassword(password) => {
@@ -163,32 +678,43 @@ impl Component<Message> for Manager {
 
     fn view(&self, state: &Self::State) -> Element<Self::Event> {
         let password = state.password.clone();
+        let filter = state.filter.clone();
+        let show_archived = state.show_archived;
         Modal::new(
             row![
-                Responsive::new(|Size { width, .. }| {
-                    Scrollable::new(
-                        Wrap::with_elements(
-                            self.menu
-                                .iter()
-                                .cloned()
-                                .enumerate()
-                                .map(|(i, item)| {
-                                    crate::item::component::Item::from(item)
-                                        .on_press(Event::EditItem(i))
-                                        .on_toggle(move |b| Event::ToggleItem(i, b))
-                                        .width(Length::Fixed(
-                                            width / 3.0 - 2.0 * DEF_PADDING as f32,
-                                        ))
-                                        .into()
-                                })
-                                .collect(),
+                column![
+                    filter.view().map(Event::Filter),
+                    Checkbox::new("Visa arkiverade", show_archived)
+                        .text_size(SMALL_TEXT::size())
+                        .on_toggle(Event::ShowArchived),
+                    Responsive::new(move |Size { width, .. }| {
+                        Scrollable::new(
+                            Wrap::with_elements(
+                                self.menu
+                                    .iter()
+                                    .enumerate()
+                                    .filter(|(_, item)| {
+                                        filter.matches(item)
+                                            && (show_archived || !item.is_archived())
+                                    })
+                                    .map(|(i, item)| {
+                                        crate::item::component::Item::from(item.clone())
+                                            .on_press(Event::EditItem(i))
+                                            .on_toggle(move |b| Event::ToggleItem(i, b))
+                                            .width(Length::Fixed(
+                                                width / 3.0 - 2.0 * DEF_PADDING as f32,
+                                            ))
+                                            .into()
+                                    })
+                                    .collect(),
+                            )
+                            .spacing(DEF_PADDING as f32)
+                            .line_spacing(DEF_PADDING as f32)
+                            .padding(DEF_PADDING as f32),
                         )
-                        .spacing(DEF_PADDING as f32)
-                        .line_spacing(DEF_PADDING as f32)
-                        .padding(DEF_PADDING as f32),
-                    )
-                    .into()
-                }),
+                        .into()
+                    }),
+                ],
                 Rule::vertical(DEF_PADDING),
                 padded_column![
                     row![
@@ -209,54 +735,469 @@ impl Component<Message> for Manager {
                         .padding(DEF_PADDING),
                     Text::new("Pris (kr)"),
                     NumberInput::new(1..=1000, Event::UpdatePrice, state.price),
+                    Text::new("Medlemspris (kr, 0 för samma)"),
+                    NumberInput::new(0..=1000, Event::UpdateMemberPrice, state.member_price),
                     Text::new("Typ"),
                     PickList::new(Category::VARIANTS, state.category, Event::UpdateCategory)
                         .width(Length::Fill),
-                    Space::with_height(Length::FillPortion(5)),
-                    if !state.locked {
-                        Button::new(BIG_TEXT::new("Spara"))
-                            .on_press(Event::Save)
-                            .padding(DEF_PADDING)
-                            .style(theme::Container::Border)
-                            .width(Length::Fill)
+                    if state.category == Some(Category::Alcohol) {
+                        column![
+                            Text::new("Alkohol (typ, cl, %)"),
+                            row![
+                                PickList::new(
+                                    AlcoholKind::VARIANTS,
+                                    state.alcohol_kind,
+                                    Event::UpdateAlcoholKind
+                                )
+                                .placeholder("Ingen"),
+                                NumberInput::new(1..=100, Event::UpdateVolume, state.volume),
+                                NumberInput::new(0.0..=100.0, Event::UpdateAbv, state.abv),
+                            ]
+                            .spacing(DEF_PADDING),
+                        ]
+                        .height(Length::Shrink)
+                        .spacing(DEF_PADDING)
+                        .into()
                     } else {
-                        Button::new(row![
-                            BIG_TEXT::new("Spara"),
-                            Space::with_width(Length::Fill),
-                            Icon::Lock,
-                        ])
-                        .on_press(Event::OpenLogin)
+                        Element::from(Space::with_height(Length::Shrink))
+                    },
+                    Text::new("Streckkod"),
+                    TextInput::new("Ingen", state.barcode.as_str())
+                        .on_input(Event::UpdateBarcode)
+                        .padding(DEF_PADDING),
+                    Text::new("Snabbtangent"),
+                    TextInput::new("Ingen", &state.hotkey.map(String::from).unwrap_or_default())
+                        .on_input(Event::UpdateHotkey)
+                        .padding(DEF_PADDING),
+                    Checkbox::new("Favorit på den här kassan", state.favorite)
+                        .on_toggle(Event::UpdateFavorite),
+                    Button::new(Text::new("Beskrivning och allergener"))
+                        .on_press(Event::OpenDetails)
                         .padding(DEF_PADDING)
                         .style(theme::Container::Border)
-                        .width(Length::Fill)
+                        .width(Length::Fill),
+                    Space::with_height(Length::FillPortion(5)),
+                    match state.mode {
+                        Mode::New => Element::from(Space::with_height(Length::Shrink)),
+                        Mode::Update(_) => column![
+                            guarded(
+                                state.locked,
+                                if state.archived {
+                                    "Återställ"
+                                } else {
+                                    "Arkivera"
+                                },
+                                Event::Archive
+                            ),
+                            guarded(state.locked, "Radera", Event::Delete),
+                        ]
+                        .height(Length::Shrink)
+                        .spacing(DEF_PADDING)
+                        .into(),
                     },
+                    guarded(state.locked, "Spara", Event::Save),
+                    Button::new(Text::new("Medlemmar"))
+                        .on_press(Event::OpenMembers)
+                        .padding(DEF_PADDING)
+                        .style(theme::Container::Border)
+                        .width(Length::Fill),
+                    Button::new(Text::new("Saldokort"))
+                        .on_press(Event::OpenAccounts)
+                        .padding(DEF_PADDING)
+                        .style(theme::Container::Border)
+                        .width(Length::Fill),
+                    Button::new(Text::new("Presentkort"))
+                        .on_press(Event::OpenVouchers)
+                        .padding(DEF_PADDING)
+                        .style(theme::Container::Border)
+                        .width(Length::Fill),
+                    Button::new(Text::new("Händelser"))
+                        .on_press(Event::OpenEvents)
+                        .padding(DEF_PADDING)
+                        .style(theme::Container::Border)
+                        .width(Length::Fill),
+                    Button::new(Text::new("Kategorier"))
+                        .on_press(Event::OpenCategories)
+                        .padding(DEF_PADDING)
+                        .style(theme::Container::Border)
+                        .width(Length::Fill),
+                    Button::new(Text::new("Importera/Exportera"))
+                        .on_press(Event::OpenTransfer)
+                        .padding(DEF_PADDING)
+                        .style(theme::Container::Border)
+                        .width(Length::Fill),
                 ]
                 .width(Length::Fixed(RECEIPT_WIDTH)),
             ],
-            state.login_modal.then(move || {
-                Card::new(
-                    Text::new("Login krävs för att ändra i produkt"),
-                    padded_column![
-                        Text::new("Lösendord"),
-                        TextInput::new("", &password)
-                            .on_input(Event::UpdatePassword)
-                            .secure(true)
-                            .padding(DEF_PADDING)
-                            .on_submit(Event::Login),
-                        Button::new(Text::new("Logga In"))
-                            .style(theme::Container::Border)
-                            .on_press(Event::Login),
-                    ]
-                    .height(Length::Shrink),
-                )
-                .max_width(650.0)
-                .on_close(Event::CloseLogin)
-            }),
+            if state.login_modal {
+                Some(Element::from(
+                    Card::new(
+                        Text::new("Login krävs för att ändra i produkt"),
+                        padded_column![
+                            Text::new("Lösendord"),
+                            TextInput::new("", &password)
+                                .on_input(Event::UpdatePassword)
+                                .secure(true)
+                                .padding(DEF_PADDING)
+                                .on_submit(Event::Login),
+                            Button::new(Text::new("Logga In"))
+                                .style(theme::Container::Border)
+                                .on_press(Event::Login),
+                        ]
+                        .height(Length::Shrink),
+                    )
+                    .max_width(650.0)
+                    .on_close(Event::CloseLogin),
+                ))
+            } else if let (true, Mode::Update(name)) = (state.delete_modal, &state.mode) {
+                Some(delete_card(name))
+            } else if state.details_modal {
+                Some(details_card(state))
+            } else if state.members_modal {
+                Some(members_card(state))
+            } else if state.accounts_modal {
+                Some(accounts_card(state))
+            } else if state.vouchers_modal {
+                Some(vouchers_card(state))
+            } else if state.events_modal {
+                Some(events_card(&self.events, state))
+            } else if state.categories_modal {
+                Some(categories_card(&self.age_checked, state.locked))
+            } else if state.transfer_modal {
+                Some(transfer_card(
+                    &state.transfer_path,
+                    state.preview.as_ref(),
+                    state.locked,
+                ))
+            } else {
+                None
+            },
         )
         .into()
     }
 }
 
+fn details_card<'a>(state: &State) -> Element<'a, Event> {
+    Card::new(
+        Text::new(format!("Beskrivning och allergener för {}", state.name)),
+        padded_column![
+            Text::new("Beskrivning"),
+            TextInput::new("Ingen", &state.description)
+                .on_input(Event::UpdateDescription)
+                .padding(DEF_PADDING),
+            Text::new("Allergener"),
+            Wrap::with_elements(
+                Allergen::VARIANTS
+                    .iter()
+                    .map(|a| {
+                        Checkbox::new(a.to_string(), state.allergens.contains(*a))
+                            .text_size(SMALL_TEXT::size())
+                            .on_toggle(|b| Event::ToggleAllergen(*a, b))
+                            .into()
+                    })
+                    .collect(),
+            )
+            .spacing(DEF_PADDING as f32)
+            .line_spacing(DEF_PADDING as f32),
+            Text::new("Bild (sökväg till fil)"),
+            row![
+                TextInput::new("", &state.image_path)
+                    .on_input(Event::UpdateImagePath)
+                    .on_submit(Event::LoadImage)
+                    .padding(DEF_PADDING),
+                Button::new(Text::new("Ladda"))
+                    .on_press(Event::LoadImage)
+                    .padding(DEF_PADDING)
+                    .style(theme::Container::Border),
+            ]
+            .spacing(DEF_PADDING),
+            match &state.image {
+                Some(handle) => column![
+                    Image::new(handle.clone()).width(Length::Fixed(RECEIPT_WIDTH)),
+                    Button::new(Text::new("Ta bort bild"))
+                        .on_press(Event::RemoveImage)
+                        .padding(DEF_PADDING)
+                        .style(theme::Container::Border),
+                ]
+                .height(Length::Shrink)
+                .spacing(DEF_PADDING)
+                .into(),
+                None => Element::from(SMALL_TEXT::new("Ingen bild")),
+            },
+        ]
+        .height(Length::Shrink),
+    )
+    .max_width(650.0)
+    .on_close(Event::CloseDetails)
+    .into()
+}
+
+fn delete_card<'a>(name: &str) -> Element<'a, Event> {
+    Card::new(
+        Text::new("Radera produkt"),
+        padded_column![
+            Text::new(format!("Vill du radera {name}? Det går inte att ångra.")),
+            SMALL_TEXT::new("Produkter som har sålts kan bara arkiveras"),
+            row![
+                Button::new(Text::new("Radera"))
+                    .on_press(Event::ConfirmDelete)
+                    .padding(DEF_PADDING)
+                    .style(theme::Container::Border),
+                Space::with_width(Length::Fill),
+                Button::new(Text::new("Avbryt"))
+                    .on_press(Event::CloseDelete)
+                    .padding(DEF_PADDING)
+                    .style(theme::Container::Border),
+            ],
+        ]
+        .height(Length::Shrink),
+    )
+    .max_width(650.0)
+    .on_close(Event::CloseDelete)
+    .into()
+}
+
+fn members_card<'a>(state: &State) -> Element<'a, Event> {
+    Card::new(
+        Text::new("Lägg till eller förnya medlem"),
+        padded_column![
+            Text::new("Medlemsnummer"),
+            TextInput::new("", &state.member_number)
+                .on_input(Event::UpdateMemberNumber)
+                .padding(DEF_PADDING),
+            Text::new("Namn"),
+            TextInput::new("", &state.member_name)
+                .on_input(Event::UpdateMemberName)
+                .padding(DEF_PADDING),
+            Text::new("Giltigt t.o.m."),
+            TextInput::new("ÅÅÅÅ-MM-DD", &state.member_valid)
+                .on_input(Event::UpdateMemberValid)
+                .on_submit(Event::SaveMember)
+                .padding(DEF_PADDING),
+            guarded(state.locked, "Spara medlem", Event::SaveMember),
+        ]
+        .height(Length::Shrink),
+    )
+    .max_width(650.0)
+    .on_close(Event::CloseMembers)
+    .into()
+}
+
+fn accounts_card<'a>(state: &State) -> Element<'a, Event> {
+    Card::new(
+        Text::new("Nytt saldokort"),
+        padded_column![
+            Text::new("Kortnummer"),
+            TextInput::new("", &state.account_card)
+                .on_input(Event::UpdateAccountCard)
+                .padding(DEF_PADDING),
+            Text::new("Innehavare"),
+            TextInput::new("", &state.account_holder)
+                .on_input(Event::UpdateAccountHolder)
+                .on_submit(Event::SaveAccount)
+                .padding(DEF_PADDING),
+            guarded(state.locked, "Skapa saldokort", Event::SaveAccount),
+        ]
+        .height(Length::Shrink),
+    )
+    .max_width(650.0)
+    .on_close(Event::CloseAccounts)
+    .into()
+}
+
+fn vouchers_card<'a>(state: &State) -> Element<'a, Event> {
+    Card::new(
+        Text::new("Nytt presentkort"),
+        padded_column![
+            Text::new("Värde"),
+            NumberInput::new(1..=10000, Event::UpdateVoucherValue, state.voucher_value),
+            Text::new("Giltigt t.o.m."),
+            TextInput::new("ÅÅÅÅ-MM-DD", &state.voucher_expires)
+                .on_input(Event::UpdateVoucherExpires)
+                .on_submit(Event::IssueVoucher)
+                .padding(DEF_PADDING),
+            guarded(state.locked, "Skapa och skriv ut", Event::IssueVoucher),
+        ]
+        .height(Length::Shrink),
+    )
+    .max_width(650.0)
+    .on_close(Event::CloseVouchers)
+    .into()
+}
+
+fn events_card<'a>(events: &[events::Event], state: &State) -> Element<'a, Event> {
+    let locked = state.locked;
+    Card::new(
+        Text::new("Händelser"),
+        padded_column![
+            SMALL_TEXT::new("Kvitton kopplas till den aktiva händelsen."),
+            Column::with_children(events.iter().enumerate().map(|(i, event)| {
+                Checkbox::new(
+                    format!(
+                        "{} ({} - {})",
+                        event.name(),
+                        event.start().format("%F %R"),
+                        event.end().format("%F %R")
+                    ),
+                    event.is_active(),
+                )
+                .text_size(SMALL_TEXT::size())
+                .on_toggle(move |b| {
+                    if locked {
+                        Event::OpenLogin
+                    } else {
+                        Event::ActivateEvent(i, b)
+                    }
+                })
+                .into()
+            }))
+            .spacing(DEF_PADDING),
+            Rule::horizontal(DEF_PADDING),
+            Text::new("Ny händelse"),
+            TextInput::new("Namn", &state.event_name)
+                .on_input(Event::UpdateEventName)
+                .padding(DEF_PADDING),
+            row![
+                TextInput::new("Start ÅÅÅÅ-MM-DD TT:MM", &state.event_start)
+                    .on_input(Event::UpdateEventStart)
+                    .padding(DEF_PADDING),
+                TextInput::new("Slut ÅÅÅÅ-MM-DD TT:MM", &state.event_end)
+                    .on_input(Event::UpdateEventEnd)
+                    .padding(DEF_PADDING),
+            ]
+            .spacing(DEF_PADDING),
+            guarded(state.locked, "Skapa", Event::CreateEvent),
+        ]
+        .height(Length::Shrink),
+    )
+    .max_width(650.0)
+    .on_close(Event::CloseEvents)
+    .into()
+}
+
+fn categories_card<'a>(age_checked: &[Category], locked: bool) -> Element<'a, Event> {
+    Card::new(
+        Text::new("Kategorier"),
+        padded_column![
+            SMALL_TEXT::new(
+                "Kunden behöver visa legitimation för att köpa produkter i markerade kategorier."
+            ),
+            Column::with_children(Category::VARIANTS.iter().map(|c| {
+                Checkbox::new(
+                    format!("{c} kräver ålderskontroll"),
+                    age_checked.contains(c),
+                )
+                .on_toggle(move |b| {
+                    if locked {
+                        Event::OpenLogin
+                    } else {
+                        Event::ToggleAgeCheck(*c, b)
+                    }
+                })
+                .into()
+            }))
+            .spacing(DEF_PADDING),
+        ]
+        .height(Length::Shrink),
+    )
+    .max_width(650.0)
+    .on_close(Event::CloseCategories)
+    .into()
+}
+
+fn transfer_card<'a>(path: &str, preview: Option<&Import>, locked: bool) -> Element<'a, Event> {
+    let names = |items: &[Item]| {
+        items
+            .iter()
+            .map(|item| item.name().as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    Card::new(
+        Text::new("Importera/Exportera meny"),
+        padded_column![
+            row![
+                Button::new(Text::new("Exportera CSV"))
+                    .on_press(Event::Export(Format::Csv))
+                    .padding(DEF_PADDING)
+                    .style(theme::Container::Border),
+                Button::new(Text::new("Exportera JSON"))
+                    .on_press(Event::Export(Format::Json))
+                    .padding(DEF_PADDING)
+                    .style(theme::Container::Border),
+            ]
+            .spacing(DEF_PADDING),
+            Rule::horizontal(DEF_PADDING),
+            Text::new("Importera från fil (.csv/.json)"),
+            TextInput::new("", path)
+                .on_input(Event::UpdateTransferPath)
+                .on_submit(Event::Preview)
+                .padding(DEF_PADDING),
+            match preview {
+                None => Element::from(
+                    Button::new(Text::new("Förhandsgranska"))
+                        .on_press(Event::Preview)
+                        .padding(DEF_PADDING)
+                        .style(theme::Container::Border),
+                ),
+                Some(import) => column![
+                    SMALL_TEXT::new(format!(
+                        "Nya ({}): {}",
+                        import.create.len(),
+                        names(&import.create)
+                    )),
+                    SMALL_TEXT::new(format!(
+                        "Ändras ({}): {}",
+                        import.update.len(),
+                        names(&import.update)
+                    )),
+                    SMALL_TEXT::new(format!("Oförändrade: {}st", import.untouched.len())),
+                    SMALL_TEXT::new(format!(
+                        "Arkiverade, återställ dem för att importera ({}): {}",
+                        import.archived.len(),
+                        names(&import.archived)
+                    )),
+                    SMALL_TEXT::new(format!(
+                        "Används av kassan och importeras inte ({}): {}",
+                        import.special.len(),
+                        names(&import.special)
+                    )),
+                    if import.is_empty() {
+                        Element::from(Space::with_height(Length::Shrink))
+                    } else {
+                        guarded(locked, "Verkställ", Event::Import).into()
+                    },
+                ]
+                .height(Length::Shrink)
+                .spacing(DEF_PADDING)
+                .into(),
+            },
+        ]
+        .height(Length::Shrink),
+    )
+    .max_width(650.0)
+    .on_close(Event::CloseTransfer)
+    .into()
+}
+
+//Opens the login modal instead of sending event while locked
+fn guarded(locked: bool, label: &str, event: Event) -> Button<'_, Event> {
+    if !locked {
+        Button::new(BIG_TEXT::new(label)).on_press(event)
+    } else {
+        Button::new(row![
+            BIG_TEXT::new(label),
+            Space::with_width(Length::Fill),
+            Icon::Lock,
+        ])
+        .on_press(Event::OpenLogin)
+    }
+    .padding(DEF_PADDING)
+    .style(theme::Container::Border)
+    .width(Length::Fill)
+}
+
 impl From<Manager> for Element<'_, Message> {
     fn from(manager: Manager) -> Self {
         iced::widget::component(manager)
@@ -1,27 +1,58 @@
 use {
-    super::{Message, Sideffect},
+    super::{Message, Sideffect, TabId},
     crate::{
+        customer::Display,
+        error::Result,
         icons::Icon,
+        item::filter::{self, Filter},
         theme::{self, DEF_PADDING, RECEIPT_WIDTH},
-        widgets::{calc::Calc, padded_column, row, SquareButton, BIG_TEXT},
+        widgets::{
+            calc::Calc, column, padded_column, qr::qr_code, row, KeyListener, SquareButton,
+            BIG_TEXT, SMALL_TEXT,
+        },
     },
     backend::{
-        items::Item,
-        receipts::{Payment, Receipt},
+        accounts::Account,
+        items::{Category, Item},
+        members::Member,
+        receipts::{payments::swish_qr, Payment, Receipt},
+        settings::Settings,
+        terminal::{self, PaymentState},
+        vouchers::Voucher,
     },
     chrono::Local,
     iced::{
+        keyboard::{key::Named, Key, Modifiers},
         widget::{
             image::{Handle, Image},
-            Button, Checkbox, Component, Container, Responsive, Rule, Scrollable, Space,
+            Button, Checkbox, Column, Component, Container, Responsive, Row, Rule, Scrollable,
+            Space, Text, TextInput,
         },
         Alignment, Element, Length, Size,
     },
-    iced_aw::Wrap,
+    iced_aw::{Card, Modal, Wrap},
 };
 
+//What the sales screen is loaded with, see TabId::load
+#[derive(Debug, Clone, Default)]
+pub struct MenuData {
+    pub menu: Vec<Item>,
+    pub favorites: Vec<Item>,
+    //Categories requiring age check
+    pub age_checked: Vec<Category>,
+    pub members: Vec<Member>,
+    //Prepaid accounts
+    pub accounts: Vec<Account>,
+    //Vouchers that can still be used
+    pub vouchers: Vec<Voucher>,
+    pub settings: Settings,
+    //Lookups that could not be read and are left empty, shown so they are not taken for missing
+    pub unavailable: Vec<&'static str>,
+}
+
 pub struct Menu {
-    menu: Vec<Item>,
+    data: MenuData,
+    payment: Option<PendingPayment>,
 }
 
 #[derive(Clone)]
@@ -29,6 +60,15 @@ pub struct State {
     multiplier: u32,
     receipt: Receipt,
     print: bool,
+    input: String,
+    filter: Filter,
+    //Waiting for the age check to be confirmed
+    pending: Option<Item>,
+    member_query: String,
+    account_query: String,
+    voucher_query: String,
+    //Waiting for the guest to pay with the Swish QR code
+    swish: Option<Receipt>,
 }
 
 impl Default for State {
@@ -37,6 +77,43 @@ impl Default for State {
             multiplier: 1,
             receipt: Receipt::default(),
             print: false,
+            input: String::new(),
+            filter: Filter::default(),
+            pending: None,
+            member_query: String::new(),
+            account_query: String::new(),
+            voucher_query: String::new(),
+            swish: None,
+        }
+    }
+}
+
+impl State {
+    //Age check is only needed once per receipt
+    fn add(&mut self, item: Item, age_checked: &[Category]) {
+        if !self.receipt.is_age_verified() && age_checked.contains(item.category()) {
+            self.pending = Some(item);
+        } else {
+            self.receipt.insert(item, self.multiplier as i32);
+            self.multiplier = 1;
+        }
+    }
+
+    //Prices depend on the member so everything already on the receipt is added again
+    fn set_member(&mut self, member: Option<String>, menu: &[Item]) {
+        let old = std::mem::take(&mut self.receipt);
+        self.receipt = Receipt::default()
+            .with_age_verified(old.is_age_verified())
+            .with_member(member)
+            .with_account(old.account().map(String::from))
+            .with_voucher(old.voucher().map(String::from));
+        for (item, amount) in old.iter() {
+            let item = menu
+                .iter()
+                .find(|i| i.name() == item.name())
+                .unwrap_or(item)
+                .clone();
+            self.receipt.insert(item, *amount);
         }
     }
 }
@@ -48,19 +125,249 @@ pub enum Event {
     ClearReceipt,
     TogglePrint(bool),
     Sell(Payment),
+    Typed(char),
+    Submit,
+    Erase,
+    Filter(filter::Event),
+    ConfirmAge,
+    DenyAge,
+    UpdateMemberQuery(String),
+    LookupMember,
+    ClearMember,
+    UpdateAccountQuery(String),
+    LookupAccount,
+    ClearAccount,
+    UpdateVoucherQuery(String),
+    LookupVoucher,
+    ClearVoucher,
+    ConfirmSwish,
+    CancelSwish,
+    CancelPayment,
+    RetryPayment,
+    ClosePayment,
 }
 
-impl Menu {
-    pub fn new(menu: Vec<Item>) -> Self {
-        Self { menu }
+//Card payment waiting for the guest at the terminal, kept by the app since the terminal is polled
+//in the background while the tabs are reloaded
+#[derive(Debug, Clone)]
+pub struct PendingPayment {
+    terminal: String,
+    id: String,
+    state: PaymentState,
+    receipt: Receipt,
+    print: bool,
+    //The receipt as it was before paying, given back unless the payment is confirmed
+    cart: Receipt,
+}
+
+impl PendingPayment {
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn state(&self) -> &PaymentState {
+        &self.state
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.state == PaymentState::Pending
+    }
+
+    pub fn receipt(&self) -> &Receipt {
+        &self.receipt
+    }
+
+    //A terminal that can not be reached is shown as a failed payment so it can be retried
+    async fn request(
+        terminal: String,
+        receipt: Receipt,
+        print: bool,
+        cart: Receipt,
+    ) -> Result<Message> {
+        let (id, state) = match terminal::provider(&terminal)
+            .request(receipt.sum(), &receipt.reference())
+            .await
+        {
+            Ok(id) => (id, PaymentState::Pending),
+            Err(e) => (String::new(), PaymentState::Failed(format!("{e:?}"))),
+        };
+        Ok(Message::RequestPayment(Self {
+            terminal,
+            id,
+            state,
+            receipt,
+            print,
+            cart,
+        }))
+    }
+
+    pub async fn poll(self) -> Message {
+        //A terminal that does not answer does not mean the payment failed, keep asking
+        let state = terminal::poll(&self.terminal, &self.id)
+            .await
+            .unwrap_or(PaymentState::Pending);
+        Message::UpdatePayment(Self { state, ..self })
+    }
+
+    async fn cancel(self) -> Message {
+        let provider = terminal::provider(&self.terminal);
+        //The guest may have paid right before, so the state is asked for again
+        let state = match provider.cancel(&self.id).await {
+            Ok(()) => provider.status(&self.id).await,
+            Err(e) => Err(e),
+        }
+        .unwrap_or_else(|e| PaymentState::Failed(format!("{e:?}")));
+        Message::UpdatePayment(Self { state, ..self })
+    }
+
+    //Only called once the terminal has confirmed the payment
+    pub fn sell(self) -> Message {
+        sell(self.receipt, self.print)
     }
 }
 
-impl Component<Message> for Menu {
-    type State = State;
-    type Event = Event;
+impl Menu {
+    pub fn new(data: MenuData) -> Self {
+        Self {
+            data,
+            payment: None,
+        }
+    }
+
+    pub fn payment(mut self, payment: Option<PendingPayment>) -> Self {
+        self.payment = payment;
+        self
+    }
+
+    fn find_account(&self, card: Option<&str>) -> Option<&Account> {
+        self.data
+            .accounts
+            .iter()
+            .find(|a| Some(a.card().as_str()) == card)
+    }
+
+    fn find_voucher(&self, code: Option<&str>) -> Option<&Voucher> {
+        self.data
+            .vouchers
+            .iter()
+            .find(|v| Some(v.code().as_str()) == code)
+    }
+
+    //Partial redemptions are fine but the voucher has to cover the whole receipt
+    fn check_voucher(&self, receipt: &Receipt, payment: Payment) -> Option<Message> {
+        if payment != Payment::Voucher {
+            return None;
+        }
+        match self.find_voucher(receipt.voucher()) {
+            None => Some(Message::OpenModal {
+                title: "Inget presentkort",
+                content: String::from("Läs in ett presentkort först"),
+            }),
+            Some(voucher) if *voucher.remaining() < receipt.sum() => Some(Message::OpenModal {
+                title: "Otillräckligt värde",
+                content: format!(
+                    "Presentkortet har {} kr kvar, kvittot är på {} kr",
+                    voucher.remaining(),
+                    receipt.sum()
+                ),
+            }),
+            Some(_) => None,
+        }
+    }
+
+    //Balances are only kept in the register's own database when it syncs, so cards and vouchers
+    //could be spent again on every other register
+    fn syncing(&self) -> bool {
+        self.data.settings.sync_server().is_some()
+    }
+
+    fn check_syncing(&self, receipt: &Receipt, payment: Payment) -> Option<Message> {
+        let balance =
+            matches!(payment, Payment::Account | Payment::Voucher) || receipt.top_up() != 0;
+        (balance && self.syncing()).then(|| Message::OpenModal {
+            title: "Inte på en kassa som synkar",
+            content: String::from("Saldokort och presentkort kan inte användas när kassan synkar"),
+        })
+    }
+
+    //Reason the sale can not be made, checked against the balance from when the tab was loaded
+    //since the backend has the final say
+    fn check_account(&self, receipt: &Receipt, payment: Payment) -> Option<Message> {
+        if payment != Payment::Account && receipt.top_up() == 0 {
+            return None;
+        }
+        let Some(account) = self.find_account(receipt.account()) else {
+            return Some(Message::OpenModal {
+                title: "Inget saldokort",
+                content: String::from("Läs in ett saldokort först"),
+            });
+        };
+        if payment != Payment::Account {
+            None
+        } else if receipt.top_up() != 0 {
+            Some(Message::OpenModal {
+                title: "Ogiltig betalning",
+                content: String::from("Saldo kan inte betalas med saldo"),
+            })
+        } else if *account.balance() < receipt.sum() {
+            Some(Message::OpenModal {
+                title: "Otillräckligt saldo",
+                content: format!(
+                    "{} har {} kr kvar, kvittot är på {} kr",
+                    account.holder(),
+                    account.balance(),
+                    receipt.sum()
+                ),
+            })
+        } else {
+            None
+        }
+    }
+
+    //Exact member number or a unique part of a name
+    fn find_member(&self, query: &str) -> Result<&Member, String> {
+        if let Some(member) = self.data.members.iter().find(|m| m.number() == query) {
+            return Ok(member);
+        }
+
+        let query = query.to_lowercase();
+        let found = self
+            .data
+            .members
+            .iter()
+            .filter(|m| m.name().to_lowercase().contains(&query))
+            .collect::<Vec<_>>();
+        match found[..] {
+            [member] => Ok(member),
+            [] => Err(String::from("Ingen medlem hittades")),
+            _ => Err(format!(
+                "Flera medlemmar matchar: {}",
+                found
+                    .iter()
+                    .map(|m| format!("{} ({})", m.name(), m.number()))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+        }
+    }
+
+    fn handle(&mut self, state: &mut State, event: Event) -> Option<Message> {
+        //Keys and scanners keep firing while the age check or a payment is open
+        if state.pending.is_some() && !matches!(event, Event::ConfirmAge | Event::DenyAge) {
+            return None;
+        }
+        if state.swish.is_some() && !matches!(event, Event::ConfirmSwish | Event::CancelSwish) {
+            return None;
+        }
+        if self.payment.is_some()
+            && !matches!(
+                event,
+                Event::CancelPayment | Event::RetryPayment | Event::ClosePayment
+            )
+        {
+            return None;
+        }
 
-    fn update(&mut self, state: &mut Self::State, event: Self::Event) -> Option<Message> {
         match event {
             Event::Multiplier(m) => {
                 state.multiplier = m;
@@ -69,70 +376,323 @@ impl Component<Message> for Menu {
                 state.receipt = Receipt::default();
             }
             Event::SellItem(i) => {
-                let item = self.menu[i].clone();
-                state.receipt.insert(item, state.multiplier as i32);
+                let item = self.data.menu[i].clone();
+                state.add(item, &self.data.age_checked);
+            }
+            //Letters are hotkeys unless a barcode or amount is being typed, since barcodes can
+            //contain letters. A letter that is no hotkey starts a barcode
+            Event::Typed(c) if state.input.is_empty() && c.is_alphabetic() => {
+                let hotkey = c.to_lowercase().next();
+                match self.data.menu.iter().find(|item| *item.hotkey() == hotkey) {
+                    Some(item) => state.add(item.clone(), &self.data.age_checked),
+                    None => state.input.push(c),
+                }
+            }
+            Event::Typed(c) => state.input.push(c),
+            Event::Submit => {
+                let input = std::mem::take(&mut state.input);
+                //Barcodes take precedence, otherwise a number shorter than the shortest EAN
+                //barcode acts as the save key on the calc
+                if let Some(item) = self
+                    .data
+                    .menu
+                    .iter()
+                    .find(|item| item.barcode().as_deref() == Some(input.as_str()))
+                {
+                    state.add(item.clone(), &self.data.age_checked);
+                } else if let Some(m) = input.parse::<u32>().ok().filter(|_| input.len() < 8) {
+                    state.multiplier = m.max(1);
+                } else if !input.is_empty() {
+                    return Some(Message::OpenModal {
+                        title: "Okänd streckkod",
+                        content: format!("Ingen produkt har streckkoden {input}"),
+                    });
+                }
+            }
+            //Same as the clear key on the calc
+            Event::Erase if !state.input.is_empty() => state.input.clear(),
+            Event::Erase => state.multiplier = 1,
+            Event::Filter(e) => state.filter.update(e),
+            Event::ConfirmAge => {
+                state.receipt = std::mem::take(&mut state.receipt).with_age_verified(true);
+                if let Some(item) = state.pending.take() {
+                    state.add(item, &self.data.age_checked);
+                }
+            }
+            Event::DenyAge => {
+                state.pending = None;
                 state.multiplier = 1;
             }
+            Event::UpdateMemberQuery(s) => state.member_query = s,
+            Event::LookupMember => {
+                let query = std::mem::take(&mut state.member_query);
+                //Would match every member
+                if query.trim().is_empty() {
+                    return None;
+                }
+                match self.find_member(query.trim()) {
+                    Ok(member) if member.is_valid() => {
+                        state.set_member(Some(member.number().clone()), &self.data.menu)
+                    }
+                    Ok(member) => {
+                        return Some(Message::OpenModal {
+                            title: "Ogiltigt medlemskap",
+                            content: format!(
+                                "Medlemskapet för {} gick ut {}",
+                                member.name(),
+                                member.valid_until().format("%F")
+                            ),
+                        })
+                    }
+                    Err(content) => {
+                        return Some(Message::OpenModal {
+                            title: "Okänd medlem",
+                            content,
+                        })
+                    }
+                }
+            }
+            Event::ClearMember => state.set_member(None, &self.data.menu),
+            Event::UpdateAccountQuery(s) => state.account_query = s,
+            Event::LookupAccount => {
+                let card = std::mem::take(&mut state.account_query);
+                match self.find_account(Some(card.trim())) {
+                    Some(account) => {
+                        state.receipt = std::mem::take(&mut state.receipt)
+                            .with_account(Some(account.card().clone()))
+                    }
+                    None => {
+                        return Some(Message::OpenModal {
+                            title: "Okänt saldokort",
+                            content: format!("Inget saldokort har nummer {}", card.trim()),
+                        })
+                    }
+                }
+            }
+            Event::ClearAccount => {
+                state.receipt = std::mem::take(&mut state.receipt).with_account(None)
+            }
+            Event::UpdateVoucherQuery(s) => state.voucher_query = s,
+            Event::LookupVoucher => {
+                let code = std::mem::take(&mut state.voucher_query)
+                    .trim()
+                    .to_uppercase();
+                match self.find_voucher(Some(&code)) {
+                    Some(voucher) if voucher.is_valid() => {
+                        state.receipt = std::mem::take(&mut state.receipt)
+                            .with_voucher(Some(voucher.code().clone()))
+                    }
+                    _ => {
+                        return Some(Message::OpenModal {
+                            title: "Ogiltigt presentkort",
+                            content: format!(
+                                "Presentkortet {code} finns inte, är förbrukat eller har gått ut"
+                            ),
+                        })
+                    }
+                }
+            }
+            Event::ClearVoucher => {
+                state.receipt = std::mem::take(&mut state.receipt).with_voucher(None)
+            }
             Event::TogglePrint(b) => state.print = b,
             Event::Sell(p) => {
                 if !state.receipt.is_empty() {
-                    let receipt = std::mem::take(&mut state.receipt)
+                    if let Some(msg) = self
+                        .check_syncing(&state.receipt, p)
+                        .or_else(|| self.check_account(&state.receipt, p))
+                        .or_else(|| self.check_voucher(&state.receipt, p))
+                    {
+                        return Some(msg);
+                    }
+                    let mut receipt = state
+                        .receipt
+                        .clone()
                         .with_payment(p)
-                        .with_time(Local::now());
-                    let should_print = state.print;
+                        .with_time(Local::now())
+                        .issue_tickets();
+                    //A voucher that was read but not paid with is not redeemed
+                    if p != Payment::Voucher {
+                        receipt = receipt.with_voucher(None);
+                    }
+                    //The guest scans the exact amount, the sale is stored once the payment is seen
+                    if p == Payment::Swish && self.data.settings.swish_number().is_some() {
+                        state.swish = Some(receipt);
+                    } else if let Some(terminal) = self
+                        .data
+                        .settings
+                        .payment_terminal()
+                        .clone()
+                        .filter(|_| p == Payment::Card)
+                    {
+                        let cart = std::mem::take(&mut state.receipt);
+                        let print = state.print;
+                        return Some(
+                            Sideffect::new(|| {
+                                PendingPayment::request(terminal, receipt, print, cart)
+                            })
+                            .into(),
+                        );
+                    } else {
+                        state.receipt = Receipt::default();
+                        return Some(sell(receipt, state.print));
+                    }
+                }
+            }
+            Event::ConfirmSwish => {
+                if let Some(receipt) = state.swish.take() {
+                    state.receipt = Receipt::default();
+                    return Some(sell(receipt, state.print));
+                }
+            }
+            Event::CancelSwish => state.swish = None,
+            Event::CancelPayment => {
+                if let Some(payment) = self.payment.clone() {
                     return Some(
-                        Sideffect::new(|| async move {
-                            if should_print {
-                                receipt.print().await?;
-                            }
-
-                            receipt.insert_sale().await?;
-                            Ok(Message::None)
-                        })
-                        .into(),
+                        Sideffect::new(|| async move { Ok(payment.cancel().await) }).into(),
+                    );
+                }
+            }
+            //Same receipt and print choice as the first attempt
+            Event::RetryPayment => {
+                if let Some(PendingPayment {
+                    terminal,
+                    receipt,
+                    print,
+                    cart,
+                    ..
+                }) = self.payment.clone()
+                {
+                    return Some(
+                        Sideffect::new(|| PendingPayment::request(terminal, receipt, print, cart))
+                            .into(),
                     );
                 }
             }
+            //Only reached when the payment was not confirmed, a confirmed payment is sold and
+            //closed by the app. The cart is given back so it can be paid again or another way
+            Event::ClosePayment => {
+                if let Some(payment) = &self.payment {
+                    state.receipt = payment.cart.clone();
+                }
+                return Some(Message::ClosePayment);
+            }
         };
         None
     }
+}
+
+impl Component<Message> for Menu {
+    type State = State;
+    type Event = Event;
+
+    fn update(&mut self, state: &mut Self::State, event: Self::Event) -> Option<Message> {
+        let msg = self.handle(state, event);
+        if !*self.data.settings.customer_display() {
+            return msg;
+        }
+
+        //The customer window follows the receipt after every change
+        let display = Message::UpdateDisplay(Box::new(Display::new(
+            state.receipt.clone(),
+            self.data
+                .settings
+                .swish_number()
+                .clone()
+                .zip(state.swish.clone()),
+            self.data.settings.clone(),
+        )));
+        Some(match msg {
+            Some(msg) => Message::Batch(vec![msg, display]),
+            None => display,
+        })
+    }
 
     fn view(&self, state: &Self::State) -> Element<Self::Event> {
         let State {
             multiplier,
             receipt,
             print,
+            input,
+            filter,
+            pending,
+            member_query,
+            account_query,
+            voucher_query,
+            swish,
         } = state.clone();
-        row![
-            Container::new(Calc::new(multiplier, Event::Multiplier))
+        let member = receipt.member().map(|number| {
+            self.data
+                .members
+                .iter()
+                .find(|m| m.number() == number)
+                .map(|m| format!("{} ({number})", m.name()))
+                .unwrap_or_else(|| number.to_string())
+        });
+        let account = self.find_account(receipt.account()).map(|a| {
+            format!(
+                "Saldokort: {} ({}), {} kr",
+                a.holder(),
+                a.card(),
+                a.balance()
+            )
+        });
+        let voucher = self
+            .find_voucher(receipt.voucher())
+            .map(|v| format!("Presentkort: {}, {} kr kvar", v.code(), v.remaining()));
+        let content = row![
+            Container::new(Calc::new(multiplier, Event::Multiplier).typed(input))
                 .padding(DEF_PADDING)
                 .center_x()
                 .center_y()
                 .width(Length::Fixed(RECEIPT_WIDTH))
                 .height(Length::Fill),
             Rule::vertical(DEF_PADDING),
-            Responsive::new(|Size { width, .. }| {
-                Scrollable::new(
-                    Wrap::with_elements(
-                        self.menu
-                            .iter()
-                            .cloned()
-                            .enumerate()
-                            .map(|(i, item)| {
-                                crate::item::component::Item::from(item)
-                                    .on_press(Event::SellItem(i))
-                                    .width(Length::Fixed(width / 3.0 - 2.0 * DEF_PADDING as f32))
-                                    .into()
-                            })
-                            .collect(),
+            column![
+                filter.view().map(Event::Filter),
+                Row::with_children(self.data.favorites.iter().filter_map(|fav| {
+                    let i = self
+                        .data
+                        .menu
+                        .iter()
+                        .position(|item| item.name() == fav.name())?;
+                    Some(
+                        crate::item::component::Item::from(fav.clone())
+                            .on_press(Event::SellItem(i))
+                            .width(Length::Fill)
+                            .into(),
                     )
-                    .align_items(Alignment::End)
-                    .spacing(DEF_PADDING as f32)
-                    .line_spacing(DEF_PADDING as f32)
-                    .padding(DEF_PADDING as f32),
-                )
-                .into()
-            }),
+                }))
+                .spacing(DEF_PADDING)
+                .padding(DEF_PADDING),
+                Rule::horizontal(DEF_PADDING),
+                Responsive::new(move |Size { width, .. }| {
+                    Scrollable::new(
+                        Wrap::with_elements(
+                            self.data
+                                .menu
+                                .iter()
+                                .enumerate()
+                                .filter(|(_, item)| filter.matches(item))
+                                .map(|(i, item)| {
+                                    crate::item::component::Item::from(item.clone())
+                                        .on_press(Event::SellItem(i))
+                                        .details(true)
+                                        .width(Length::Fixed(
+                                            width / 3.0 - 2.0 * DEF_PADDING as f32,
+                                        ))
+                                        .into()
+                                })
+                                .collect(),
+                        )
+                        .align_items(Alignment::End)
+                        .spacing(DEF_PADDING as f32)
+                        .line_spacing(DEF_PADDING as f32)
+                        .padding(DEF_PADDING as f32),
+                    )
+                    .into()
+                }),
+            ],
             Rule::vertical(DEF_PADDING),
             padded_column![
                 row![
@@ -141,19 +701,96 @@ impl Component<Message> for Menu {
                     SquareButton::icon(Icon::Cross).on_press(Event::ClearReceipt),
                 ]
                 .align_items(Alignment::Center),
+                if self.data.unavailable.is_empty() {
+                    Element::from(Space::with_height(Length::Shrink))
+                } else {
+                    SMALL_TEXT::new(format!(
+                        "Kunde inte läsas in: {}",
+                        self.data.unavailable.join(", ")
+                    ))
+                    .into()
+                },
+                match member {
+                    Some(member) => row![
+                        Text::new(format!("Medlem: {member}")).width(Length::Fill),
+                        SquareButton::icon(Icon::Cross).on_press(Event::ClearMember),
+                    ]
+                    .align_items(Alignment::Center),
+                    None => row![TextInput::new("Medlemsnummer eller namn", &member_query)
+                        .on_input(Event::UpdateMemberQuery)
+                        .on_submit(Event::LookupMember)
+                        .padding(DEF_PADDING)],
+                },
+                match (self.syncing(), account) {
+                    (true, _) => Element::from(Space::with_height(Length::Shrink)),
+                    (false, Some(account)) => row![
+                        Text::new(account).width(Length::Fill),
+                        SquareButton::icon(Icon::Cross).on_press(Event::ClearAccount),
+                    ]
+                    .align_items(Alignment::Center)
+                    .into(),
+                    (false, None) => row![TextInput::new("Saldokort", &account_query)
+                        .on_input(Event::UpdateAccountQuery)
+                        .on_submit(Event::LookupAccount)
+                        .padding(DEF_PADDING)]
+                    .into(),
+                },
+                match (self.syncing(), voucher) {
+                    (true, _) => Element::from(Space::with_height(Length::Shrink)),
+                    (false, Some(voucher)) => row![
+                        Text::new(voucher).width(Length::Fill),
+                        SquareButton::icon(Icon::Cross).on_press(Event::ClearVoucher),
+                    ]
+                    .align_items(Alignment::Center)
+                    .into(),
+                    (false, None) => row![TextInput::new("Presentkort", &voucher_query)
+                        .on_input(Event::UpdateVoucherQuery)
+                        .on_submit(Event::LookupVoucher)
+                        .padding(DEF_PADDING)]
+                    .into(),
+                },
                 crate::receipt::Receipt::from(receipt),
                 Checkbox::new("Printa kvitto", print)
                     .text_size(30)
                     .width(Length::Fill)
                     .on_toggle(Event::TogglePrint),
                 row![
-                    payment_to_button(Payment::Swish),
-                    payment_to_button(Payment::Paypal)
+                    payment_to_button(Payment::Swish, "F1"),
+                    payment_to_button(Payment::Paypal, "F2"),
                 ]
+                .spacing(DEF_PADDING),
+                Row::with_children(
+                    [
+                        (Payment::Card, "F5"),
+                        (Payment::Account, "F3"),
+                        (Payment::Voucher, "F4"),
+                    ]
+                    .into_iter()
+                    .filter(|(p, _)| *p == Payment::Card || !self.syncing())
+                    .map(|(p, hotkey)| payment_to_button(p, hotkey).into()),
+                )
                 .spacing(DEF_PADDING)
             ]
             .width(Length::Fixed(RECEIPT_WIDTH)),
-        ]
+        ];
+
+        Modal::new(
+            KeyListener::new(content, on_key),
+            self.payment
+                .as_ref()
+                .map(payment_card)
+                .or_else(|| pending.map(|item| age_card(&item)))
+                .or_else(|| {
+                    let payee = self.data.settings.swish_number().as_deref()?;
+                    swish.as_ref().map(|receipt| swish_card(payee, receipt))
+                }),
+        )
+        .backdrop(match (&self.payment, swish) {
+            (Some(payment), _) if payment.is_pending() => Event::CancelPayment,
+            (Some(_), _) => Event::ClosePayment,
+            (None, Some(_)) => Event::CancelSwish,
+            (None, None) => Event::DenyAge,
+        })
         .into()
     }
 }
@@ -164,16 +801,235 @@ impl From<Menu> for Element<'_, Message> {
     }
 }
 
-fn payment_to_button<'a>(p: Payment) -> Button<'a, Event> {
-    let image = Image::new(Handle::from_memory(match p {
-        Payment::Swish => include_bytes!("../../resources/swish.png").to_vec(),
-        Payment::Paypal => include_bytes!("../../resources/paypal.png").to_vec(),
-        _ => unreachable!("Payment to Image"),
-    }));
+fn age_card<'a>(item: &Item) -> Element<'a, Event> {
+    Card::new(
+        Text::new("Ålderskontroll"),
+        padded_column![
+            Text::new(format!(
+                "{} kräver att kunden har uppnått åldersgränsen. Kontrollera legitimation.",
+                item.name()
+            )),
+            row![
+                Button::new(Text::new("Legitimation kontrollerad"))
+                    .on_press(Event::ConfirmAge)
+                    .padding(DEF_PADDING)
+                    .style(theme::Container::Border),
+                Space::with_width(Length::Fill),
+                Button::new(Text::new("Avbryt"))
+                    .on_press(Event::DenyAge)
+                    .padding(DEF_PADDING)
+                    .style(theme::Container::Border),
+            ],
+        ]
+        .height(Length::Shrink),
+    )
+    .max_width(650.0)
+    .on_close(Event::DenyAge)
+    .into()
+}
+
+fn swish_card<'a>(payee: &str, receipt: &Receipt) -> Element<'a, Event> {
+    let reference = receipt.reference();
+    Card::new(
+        Text::new("Swish"),
+        padded_column![
+            match qr_code(&swish_qr(payee, receipt.sum(), &reference)) {
+                Some(qr) => Element::from(qr),
+                None => Element::from(Text::new("QR-koden kunde inte skapas")),
+            },
+            BIG_TEXT::new(format!("{} kr", receipt.sum())),
+            SMALL_TEXT::new(format!("Till {payee}, meddelande \"{reference}\"")),
+            row![
+                Button::new(Text::new("Betalning mottagen"))
+                    .on_press(Event::ConfirmSwish)
+                    .padding(DEF_PADDING)
+                    .style(theme::Container::Border),
+                Space::with_width(Length::Fill),
+                Button::new(Text::new("Avbryt"))
+                    .on_press(Event::CancelSwish)
+                    .padding(DEF_PADDING)
+                    .style(theme::Container::Border),
+            ],
+        ]
+        .align_items(Alignment::Center)
+        .height(Length::Shrink),
+    )
+    .max_width(650.0)
+    .on_close(Event::CancelSwish)
+    .into()
+}
+
+fn payment_card<'a>(payment: &PendingPayment) -> Element<'a, Event> {
+    let sum = format!("{} kr", payment.receipt.sum());
+    let (info, buttons) = match &payment.state {
+        PaymentState::Pending | PaymentState::Confirmed => (
+            String::from("Väntar på att kunden betalar i terminalen"),
+            row![Button::new(Text::new("Avbryt"))
+                .on_press(Event::CancelPayment)
+                .padding(DEF_PADDING)
+                .style(theme::Container::Border)],
+        ),
+        state => (
+            match state {
+                PaymentState::Failed(reason) => format!("Betalningen misslyckades: {reason}"),
+                _ => String::from("Betalningen avbröts"),
+            },
+            row![
+                Button::new(Text::new("Försök igen"))
+                    .on_press(Event::RetryPayment)
+                    .padding(DEF_PADDING)
+                    .style(theme::Container::Border),
+                Space::with_width(Length::Fill),
+                Button::new(Text::new("Stäng"))
+                    .on_press(Event::ClosePayment)
+                    .padding(DEF_PADDING)
+                    .style(theme::Container::Border),
+            ],
+        ),
+    };
+
+    Card::new(
+        Text::new("Kortbetalning"),
+        padded_column![BIG_TEXT::new(sum), Text::new(info), buttons]
+            .align_items(Alignment::Center)
+            .height(Length::Shrink),
+    )
+    .max_width(650.0)
+    .into()
+}
+
+fn sell(receipt: Receipt, should_print: bool) -> Message {
+    Sideffect::new(|| async move {
+        //Balances changed, reload them
+        let reload = receipt.account().is_some() || receipt.voucher().is_some();
+        let receipt =
+            match receipt.insert_sale().await {
+                Err(backend::Error::InsufficientFunds) => {
+                    return Ok(Message::OpenModal {
+                        title: "Otillräckligt saldo",
+                        content: String::from("Saldot räcker inte, kvittot sparades inte"),
+                    })
+                }
+                Err(backend::Error::InvalidAmount) => {
+                    return Ok(Message::OpenModal {
+                        title: "Ogiltigt belopp",
+                        content: String::from(
+                            "Kvittot måste vara på mer än 0 kr för att betalas med saldo eller \
+                         presentkort, kvittot sparades inte",
+                        ),
+                    })
+                }
+                Err(backend::Error::NotWhileSyncing) => return Ok(Message::OpenModal {
+                    title: "Inte på en kassa som synkar",
+                    content: String::from(
+                        "Saldokort och presentkort kan inte användas när kassan synkar, kvittot \
+                         sparades inte",
+                    ),
+                }),
+                Err(backend::Error::VoucherNotValid) => {
+                    return Ok(Message::OpenModal {
+                        title: "Ogiltigt presentkort",
+                        content: String::from(
+                            "Presentkortet räcker inte eller har gått ut, kvittot sparades inte",
+                        ),
+                    })
+                }
+                res => res?,
+            };
+
+        //The sale is stored at this point, so printing problems must not look like it failed
+        let drawer_error = receipt.open_drawer().await.err();
+        let print_error = match should_print {
+            true => receipt.print().await.err(),
+            false => None,
+        };
+        if let Some(e) = receipt.kitchen_error() {
+            return Ok(Message::OpenModal {
+                title: "Köksbongen skrevs inte ut",
+                content: format!(
+                    "Kvittot sparades men köksbongen för order {} kunde inte skrivas ut, \
+                     meddela köket: {e}",
+                    receipt.order().unwrap_or_default()
+                ),
+            });
+        }
+        if let Some(e) = print_error {
+            return Ok(Message::OpenModal {
+                title: "Kvittot skrevs inte ut",
+                content: format!(
+                    "Kvittot sparades men kunde inte skrivas ut, skriv ut det igen under \
+                     Kvitton: {e:?}"
+                ),
+            });
+        }
+        if let Some(e) = drawer_error {
+            return Ok(Message::OpenModal {
+                title: "Kassalådan öppnades inte",
+                content: format!("Kvittot sparades men kassalådan kunde inte öppnas: {e:?}"),
+            });
+        }
+        if let (false, Some(order)) = (should_print, receipt.order()) {
+            //The number is only on the receipt, so it has to be told to the customer
+            return Ok(Message::OpenModal {
+                title: "Order",
+                content: format!("Ordernummer {order}, ropas upp när maten är klar"),
+            });
+        }
+
+        if reload {
+            TabId::Menu.load().await
+        } else {
+            Ok(Message::None)
+        }
+    })
+    .into()
+}
+
+//Typed characters are either a hotkey, a multiplier or a barcode, barcode scanners act as
+//keyboards typing the code followed by enter
+fn on_key(key: Key, modifiers: Modifiers) -> Option<Event> {
+    if modifiers.control() || modifiers.alt() || modifiers.logo() {
+        return None;
+    }
+
+    match key.as_ref() {
+        Key::Character(s) => s
+            .chars()
+            .next()
+            .filter(|c| !c.is_control())
+            .map(Event::Typed),
+        Key::Named(Named::Enter) => Some(Event::Submit),
+        Key::Named(Named::Backspace) => Some(Event::Erase),
+        Key::Named(Named::F1) => Some(Event::Sell(Payment::Swish)),
+        Key::Named(Named::F2) => Some(Event::Sell(Payment::Paypal)),
+        Key::Named(Named::F3) => Some(Event::Sell(Payment::Account)),
+        Key::Named(Named::F4) => Some(Event::Sell(Payment::Voucher)),
+        Key::Named(Named::F5) => Some(Event::Sell(Payment::Card)),
+        _ => None,
+    }
+}
+
+fn payment_to_button<'a>(p: Payment, hotkey: &'a str) -> Button<'a, Event> {
+    let label: Element<Event> = match p {
+        Payment::Swish => Image::new(Handle::from_memory(
+            include_bytes!("../../resources/swish.png").to_vec(),
+        ))
+        .into(),
+        Payment::Paypal => Image::new(Handle::from_memory(
+            include_bytes!("../../resources/paypal.png").to_vec(),
+        ))
+        .into(),
+        p => Text::new(p.to_string()).into(),
+    };
 
-    Button::new(image)
-        .on_press(Event::Sell(p))
-        .padding(DEF_PADDING)
-        .style(theme::Container::Border)
-        .width(Length::Fill)
+    Button::new(
+        Column::new()
+            .push(label)
+            .push(SMALL_TEXT::new(hotkey))
+            .align_items(Alignment::Center),
+    )
+    .on_press(Event::Sell(p))
+    .padding(DEF_PADDING)
+    .style(theme::Container::Border)
+    .width(Length::Fill)
 }
@@ -2,57 +2,114 @@ pub mod info;
 pub mod manager;
 pub mod menu;
 pub mod sales;
+pub mod settings;
+pub mod tickets;
 pub mod transactions;
 
 use {
-    crate::error::{Error, Result},
-    backend::{items::Item, receipts::Receipt, summary::Summary},
+    crate::{
+        customer::Display,
+        error::{Error, Result},
+        item::images,
+    },
+    backend::{
+        accounts::Account,
+        events,
+        items::{Category, Item},
+        members::Member,
+        print_queue::{self, Job},
+        receipts::Receipt,
+        settings::Settings,
+        summary::Summary,
+        sync,
+        tickets::{Ticket, Validation},
+        vouchers::Voucher,
+    },
     chrono::{DateTime, Local, NaiveDate},
     futures::{future::BoxFuture, FutureExt},
-    iced::Element,
+    iced::{window, Element},
     indexmap::IndexMap,
     std::future::{Future, IntoFuture},
 };
 
-use {info::Info, manager::Manager, menu::Menu, sales::Sales, transactions::Transactions};
+use {
+    info::Info,
+    manager::Manager,
+    menu::{Menu, MenuData, PendingPayment},
+    sales::Sales,
+    tickets::Tickets,
+    transactions::Transactions,
+};
 
 #[derive(Clone, Debug)]
 pub enum Tab {
-    Menu(Vec<Item>),
-    Transactions(IndexMap<DateTime<Local>, Receipt>),
-    Sales(Summary),
-    Manager(Vec<Item>),
+    Menu(MenuData),
+    //Recent receipts, Pending and failed print jobs, Sync status when syncing
+    Transactions(
+        IndexMap<DateTime<Local>, Receipt>,
+        Vec<Job>,
+        Option<sync::Status>,
+    ),
+    //Summary, Selectable events
+    Sales(Summary, Vec<events::Event>),
+    //Recently sold tickets, Result of the last scan
+    Tickets(Vec<Ticket>, Option<Validation>),
+    //Menu, Items pinned on this register, Categories requiring age check, Events
+    Manager(Vec<Item>, Vec<String>, Vec<Category>, Vec<events::Event>),
+    Settings(Settings),
     Info(self_update::Status),
 }
 
 impl Tab {
-    pub fn as_menu(&self) -> Element<Message> {
-        if let Self::Menu(menu) = self {
-            Menu::new(menu.clone()).into()
+    pub fn as_menu(&self, payment: Option<&PendingPayment>) -> Element<Message> {
+        if let Self::Menu(data) = self {
+            Menu::new(data.clone()).payment(payment.cloned()).into()
         } else {
             iced::widget::Text::new("Empty").into()
         }
     }
 
     pub fn as_transactions(&self) -> Element<Message> {
-        if let Self::Transactions(transactions) = self {
-            Transactions::new(transactions.clone()).into()
+        if let Self::Transactions(transactions, jobs, sync) = self {
+            Transactions::new(transactions.clone(), jobs.clone(), sync.clone()).into()
         } else {
             iced::widget::Text::new("Empty").into()
         }
     }
 
     pub fn as_sales(&self) -> Element<Message> {
-        if let Self::Sales(summary) = self {
-            Sales::new(summary.clone()).into()
+        if let Self::Sales(summary, events) = self {
+            Sales::new(summary.clone(), events.clone()).into()
+        } else {
+            iced::widget::Text::new("Empty").into()
+        }
+    }
+
+    pub fn as_tickets(&self) -> Element<Message> {
+        if let Self::Tickets(tickets, last) = self {
+            Tickets::new(tickets.clone(), last.clone()).into()
         } else {
             iced::widget::Text::new("Empty").into()
         }
     }
 
     pub fn as_manager(&self) -> Element<Message> {
-        if let Self::Manager(menu) = self {
-            Manager::new(menu.clone()).into()
+        if let Self::Manager(menu, pinned, age_checked, events) = self {
+            Manager::new(
+                menu.clone(),
+                pinned.clone(),
+                age_checked.clone(),
+                events.clone(),
+            )
+            .into()
+        } else {
+            iced::widget::Text::new("Empty").into()
+        }
+    }
+
+    pub fn as_settings(&self) -> Element<Message> {
+        if let Self::Settings(settings) = self {
+            settings::Settings::new(settings.clone()).into()
         } else {
             iced::widget::Text::new("Empty").into()
         }
@@ -68,13 +125,16 @@ impl Tab {
 
     pub fn id(&self) -> TabId {
         match self {
-            Self::Menu(_) => TabId::Menu,
-            Self::Transactions(_) => TabId::Transactions,
-            Self::Sales(summary) => TabId::Sales {
+            Self::Menu(..) => TabId::Menu,
+            Self::Transactions(..) => TabId::Transactions,
+            Self::Sales(summary, _) => TabId::Sales {
                 from: summary.from().date_naive(),
                 to: summary.to().date_naive(),
+                event: summary.event().as_ref().map(|e| *e.id()),
             },
-            Self::Manager(_) => TabId::Manager,
+            Self::Tickets(..) => TabId::Tickets,
+            Self::Manager(..) => TabId::Manager,
+            Self::Settings(_) => TabId::Settings,
             Self::Info(_) => TabId::Info,
         }
     }
@@ -84,8 +144,14 @@ impl Tab {
 pub enum TabId {
     Menu,
     Transactions,
-    Sales { from: NaiveDate, to: NaiveDate },
+    Sales {
+        from: NaiveDate,
+        to: NaiveDate,
+        event: Option<i64>,
+    },
+    Tickets,
     Manager,
+    Settings,
     Info,
 }
 
@@ -96,7 +162,9 @@ impl PartialEq for TabId {
             (Self::Menu, Self::Menu)
                 | (Self::Transactions, Self::Transactions)
                 | (Self::Sales { .. }, Self::Sales { .. })
+                | (Self::Tickets, Self::Tickets)
                 | (Self::Manager, Self::Manager)
+                | (Self::Settings, Self::Settings)
                 | (Self::Info, Self::Info)
         )
     }
@@ -105,11 +173,49 @@ impl PartialEq for TabId {
 impl TabId {
     pub async fn load(self) -> Result<Message> {
         Ok(Message::LoadTab(match self {
-            Self::Menu => Tab::Menu(Item::get_all_available().await?),
+            //Selling only needs the menu, the age checks and the settings. The lookups are left
+            //empty if they can not be read, and listed on the screen
+            Self::Menu => {
+                let mut unavailable = Vec::new();
+                let menu = Item::get_all_available().await?;
+                let favorites =
+                    optional(Item::get_favorites().await, "favoriter", &mut unavailable);
+                images::load(menu.iter().chain(&favorites)).await;
+                Tab::Menu(MenuData {
+                    menu,
+                    favorites,
+                    age_checked: Category::get_age_checked().await?,
+                    members: optional(Member::get_all().await, "medlemmar", &mut unavailable),
+                    accounts: optional(Account::get_all().await, "saldokort", &mut unavailable),
+                    vouchers: optional(
+                        Voucher::get_outstanding().await,
+                        "presentkort",
+                        &mut unavailable,
+                    ),
+                    settings: Settings::get().await?,
+                    unavailable,
+                })
+            }
+
+            Self::Transactions => Tab::Transactions(
+                Receipt::get_recents().await?,
+                print_queue::jobs(),
+                sync::status().await?,
+            ),
 
-            Self::Transactions => Tab::Transactions(Receipt::get_recents().await?),
+            Self::Sales {
+                event: Some(id), ..
+            } => {
+                let events = events::Event::get_all().await?;
+                match events.iter().find(|e| *e.id() == id) {
+                    Some(event) => {
+                        Tab::Sales(Summary::get_event_summary(event.clone()).await?, events)
+                    }
+                    None => return Err(Error::Other("Händelsen finns inte".into())),
+                }
+            }
 
-            Self::Sales { from, to } => {
+            Self::Sales { from, to, .. } => {
                 let from_time = from
                     .and_hms_opt(0, 0, 0)
                     .unwrap()
@@ -122,16 +228,43 @@ impl TabId {
                     .and_local_timezone(Local)
                     .single()
                     .unwrap();
-                Tab::Sales(Summary::get_sales_summary(from_time, to_time).await?)
+                Tab::Sales(
+                    Summary::get_sales_summary(from_time, to_time).await?,
+                    events::Event::get_all().await?,
+                )
             }
 
-            Self::Manager => Tab::Manager(Item::get_all().await?),
+            Self::Tickets => Tab::Tickets(Ticket::get_recents().await?, None),
+
+            Self::Manager => {
+                let menu = Item::get_all().await?;
+                images::load(&menu).await;
+                Tab::Manager(
+                    menu,
+                    Item::get_pinned().await?,
+                    Category::get_age_checked().await?,
+                    events::Event::get_all().await?,
+                )
+            }
+
+            Self::Settings => Tab::Settings(Settings::get().await?),
 
             Self::Info => Tab::Info(crate::config::update()?),
         }))
     }
 }
 
+fn optional<T: Default>(
+    result: backend::Result<T>,
+    name: &'static str,
+    unavailable: &mut Vec<&'static str>,
+) -> T {
+    result.unwrap_or_else(|_| {
+        unavailable.push(name);
+        T::default()
+    })
+}
+
 #[derive(Clone)]
 pub struct Sideffect(futures::future::Shared<BoxFuture<'static, Result<Message>>>);
 
@@ -172,6 +305,13 @@ pub enum Message {
         content: String,
     },
     Sideffect(Sideffect),
+    RequestPayment(PendingPayment),
+    UpdatePayment(PendingPayment),
+    ClosePayment,
+    UpdateDisplay(Box<Display>),
+    WindowClosed(window::Id),
+    //Handled in order, for components that can only return one message
+    Batch(Vec<Message>),
 }
 
 impl From<()> for Message {
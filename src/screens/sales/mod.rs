@@ -5,10 +5,13 @@ use {
         theme::{self, DEF_PADDING, RECEIPT_WIDTH},
         widgets::{column, padded_column, padded_row, row, BIG_TEXT, SMALL_TEXT},
     },
-    backend::summary::Summary,
+    backend::{
+        events,
+        summary::{AlcoholReport, BalanceStatement, Summary},
+    },
     chrono::NaiveDate,
     iced::{
-        widget::{Button, Component, Container, Row, Rule, Space, Text},
+        widget::{Button, Component, Container, PickList, Row, Rule, Space, Text},
         Alignment, Element, Length,
     },
     iced_aw::date_picker::{self, DatePicker},
@@ -23,22 +26,34 @@ pub enum Picker {
 pub struct Sales {
     from: NaiveDate,
     to: NaiveDate,
+    event: Option<i64>,
     summary: Summary,
+    events: Vec<events::Event>,
 }
 
 #[derive(Debug, Clone)]
 pub enum Event {
     Save,
+    SaveAlcohol,
+    SaveBalances,
     OpenDate(Picker),
     UpdateDate(date_picker::Date),
     CloseDate,
+    SelectEvent(events::Event),
 }
 
 impl Sales {
-    pub fn new(summary: Summary) -> Self {
+    pub fn new(summary: Summary, events: Vec<events::Event>) -> Self {
         let from = summary.from().naive_local().date();
         let to = summary.to().naive_local().date();
-        Self { from, to, summary }
+        let event = summary.event().as_ref().map(|e| *e.id());
+        Self {
+            from,
+            to,
+            event,
+            summary,
+            events,
+        }
     }
 }
 
@@ -66,6 +81,44 @@ impl Component<Message> for Sales {
                     .into(),
                 );
             }
+            Event::SaveAlcohol => {
+                let from = *self.summary.from();
+                let to = *self.summary.to();
+                return Some(
+                    Sideffect::new(|| async move {
+                        let report = AlcoholReport::get(from, to).await?;
+                        if !report.is_empty() {
+                            let path = report.save().await?;
+                            Ok(Message::OpenModal {
+                                title: "Sparad",
+                                content: format!("Sparad till {}", path.to_string_lossy()),
+                            })
+                        } else {
+                            Err(Error::Other("Ingen alkoholförsäljning att spara".into()))
+                        }
+                    })
+                    .into(),
+                );
+            }
+            Event::SaveBalances => {
+                let from = *self.summary.from();
+                let to = *self.summary.to();
+                return Some(
+                    Sideffect::new(|| async move {
+                        let statement = BalanceStatement::get(from, to).await?;
+                        if !statement.is_empty() {
+                            let path = statement.save().await?;
+                            Ok(Message::OpenModal {
+                                title: "Sparad",
+                                content: format!("Sparad till {}", path.to_string_lossy()),
+                            })
+                        } else {
+                            Err(Error::Other("Inga saldokort att redovisa".into()))
+                        }
+                    })
+                    .into(),
+                );
+            }
             Event::OpenDate(p) => {
                 *state = Some(p);
                 return None;
@@ -81,16 +134,21 @@ impl Component<Message> for Sales {
                     }
                     None => (),
                 };
+                self.event = None;
                 *state = None;
             }
             Event::CloseDate => {
                 *state = None;
             }
+            Event::SelectEvent(e) => {
+                self.event = Some(*e.id());
+            }
         }
 
         let from = self.from;
         let to = self.to;
-        Some(Sideffect::new(|| async move { TabId::Sales { from, to }.load().await }).into())
+        let event = self.event;
+        Some(Sideffect::new(|| async move { TabId::Sales { from, to, event }.load().await }).into())
     }
 
     fn view(&self, state: &Self::State) -> Element<Self::Event> {
@@ -127,6 +185,14 @@ impl Component<Message> for Sales {
             padded_column![
                 BIG_TEXT::new("Visa Försäljning"),
                 Space::with_height(Length::Fill),
+                Text::new("Händelse"),
+                PickList::new(
+                    self.events.clone(),
+                    self.summary.event().clone(),
+                    Event::SelectEvent
+                )
+                .placeholder("Alla")
+                .width(Length::Fill),
                 Text::new("Fr.o.m."),
                 DatePicker::new(
                     matches!(state, Some(Picker::From)),
@@ -154,11 +220,25 @@ impl Component<Message> for Sales {
                 )
                 .font_size(SMALL_TEXT::size()),
                 Space::with_height(Length::Fill),
+                Text::new(format!(
+                    "Utestående presentkort: {} kr",
+                    self.summary.voucher_liability()
+                )),
                 Button::new(BIG_TEXT::new("Exportera"))
                     .on_press(Event::Save)
                     .padding(DEF_PADDING)
                     .style(theme::Container::Border)
                     .width(Length::Fill),
+                Button::new(Text::new("Alkoholrapport"))
+                    .on_press(Event::SaveAlcohol)
+                    .padding(DEF_PADDING)
+                    .style(theme::Container::Border)
+                    .width(Length::Fill),
+                Button::new(Text::new("Saldorapport"))
+                    .on_press(Event::SaveBalances)
+                    .padding(DEF_PADDING)
+                    .style(theme::Container::Border)
+                    .width(Length::Fill),
             ]
             .width(Length::Fixed(RECEIPT_WIDTH)),
         ]
@@ -0,0 +1,346 @@
+use {
+    super::{Message, Sideffect, TabId},
+    crate::{
+        icons::Icon,
+        theme::{self, DEF_PADDING},
+        widgets::{column, padded_column, row, NumberInput, SquareButton, BIG_TEXT, SMALL_TEXT},
+    },
+    iced::{
+        widget::{
+            Button, Checkbox, Column, Component, Container, Rule, Scrollable, Space, Text,
+            TextInput,
+        },
+        Alignment, Element, Length,
+    },
+};
+
+pub struct Settings {
+    settings: backend::settings::Settings,
+}
+
+pub struct State {
+    locked: bool,
+    password: String,
+    //Unsaved changes, the stored settings are shown until something is edited
+    draft: Option<backend::settings::Settings>,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self {
+            locked: true,
+            password: String::new(),
+            draft: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Event {
+    UpdatePassword(String),
+    Login,
+    UpdateHeader(usize, String),
+    AddHeaderLine,
+    RemoveHeaderLine(usize),
+    UpdateOrgNumber(String),
+    UpdateLogo(String),
+    UpdatePaperWidth(u32),
+    UpdateExportDir(String),
+    UpdatePrinterCommand(String),
+    UpdatePrinterName(String),
+    UpdateReceiptPrinter(String),
+    ToggleCashDrawer(bool),
+    ToggleKitchenTickets(bool),
+    UpdateKitchenPrinter(String),
+    ToggleCustomerDisplay(bool),
+    UpdateSwishNumber(String),
+    UpdatePaymentTerminal(String),
+    UpdateServer(String),
+    UpdateServerKey(String),
+    UpdateRegister(String),
+    UpdateSyncServer(String),
+    Reset,
+    Save,
+}
+
+impl Settings {
+    pub fn new(settings: backend::settings::Settings) -> Self {
+        Self { settings }
+    }
+}
+
+impl Component<Message> for Settings {
+    type State = State;
+    type Event = Event;
+
+    fn update(&mut self, state: &mut Self::State, event: Self::Event) -> Option<Message> {
+        match event {
+            Event::UpdatePassword(password) => state.password = password,
+            //No password in debug mode
+            #[cfg(debug_assertions)]
+            Event::Login => state.locked = false,
+            //Use env for password
+            #[cfg(not(debug_assertions))]
+            Event::Login => state.locked = state.password != env!("SMALANDS_PASSWORD"),
+            Event::Reset => state.draft = None,
+            Event::Save if !state.locked => {
+                let mut settings = state.draft.take()?;
+                let header = settings
+                    .header()
+                    .iter()
+                    .filter(|line| !line.trim().is_empty())
+                    .cloned()
+                    .collect();
+                settings = settings.with_header(header);
+                return Some(
+                    Sideffect::new(|| async move {
+                        settings.save().await?;
+                        TabId::Settings.load().await
+                    })
+                    .into(),
+                );
+            }
+            Event::Save => (),
+            event => {
+                let draft = state.draft.take().unwrap_or_else(|| self.settings.clone());
+                state.draft = Some(edit(draft, event));
+            }
+        }
+        None
+    }
+
+    fn view(&self, state: &Self::State) -> Element<Self::Event> {
+        if state.locked {
+            return Container::new(
+                padded_column![
+                    Text::new("Login krävs för att ändra inställningar"),
+                    TextInput::new("Lösenord", &state.password)
+                        .on_input(Event::UpdatePassword)
+                        .secure(true)
+                        .padding(DEF_PADDING)
+                        .on_submit(Event::Login),
+                    Button::new(Text::new("Logga In"))
+                        .style(theme::Container::Border)
+                        .padding(DEF_PADDING)
+                        .on_press(Event::Login),
+                ]
+                .width(Length::Fixed(650.0))
+                .height(Length::Shrink),
+            )
+            .center_x()
+            .center_y()
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .into();
+        }
+
+        let settings = state.draft.as_ref().unwrap_or(&self.settings);
+        column![
+            Scrollable::new(row![
+                receipt_column(settings),
+                Rule::vertical(DEF_PADDING),
+                printing_column(settings),
+            ])
+            .height(Length::Fill),
+            Rule::horizontal(DEF_PADDING),
+            row![
+                Space::with_width(Length::Fill),
+                Button::new(BIG_TEXT::new("Ångra"))
+                    .on_press_maybe(state.draft.is_some().then_some(Event::Reset))
+                    .padding(DEF_PADDING)
+                    .style(theme::Container::Border),
+                Button::new(BIG_TEXT::new("Spara"))
+                    .on_press_maybe(state.draft.is_some().then_some(Event::Save))
+                    .padding(DEF_PADDING)
+                    .style(theme::Container::Border),
+            ]
+            .spacing(DEF_PADDING)
+            .padding(DEF_PADDING),
+        ]
+        .into()
+    }
+}
+
+fn edit(settings: backend::settings::Settings, event: Event) -> backend::settings::Settings {
+    let mut header = settings.header().clone();
+    match event {
+        Event::UpdateHeader(i, line) => {
+            if let Some(old) = header.get_mut(i) {
+                *old = line;
+            }
+            settings.with_header(header)
+        }
+        Event::AddHeaderLine => {
+            header.push(String::new());
+            settings.with_header(header)
+        }
+        Event::RemoveHeaderLine(i) => {
+            if i < header.len() {
+                header.remove(i);
+            }
+            settings.with_header(header)
+        }
+        Event::UpdateOrgNumber(s) => settings.with_org_number(Some(s)),
+        Event::UpdateLogo(s) => settings.with_logo(Some(s)),
+        Event::UpdatePaperWidth(w) => settings.with_paper_width(w),
+        Event::UpdateExportDir(s) => settings.with_export_dir(Some(s)),
+        Event::UpdatePrinterCommand(s) => settings.with_printer_command(Some(s)),
+        Event::UpdatePrinterName(s) => settings.with_printer_name(Some(s)),
+        Event::UpdateReceiptPrinter(s) => settings.with_receipt_printer(Some(s)),
+        Event::ToggleCashDrawer(b) => settings.with_cash_drawer(b),
+        Event::ToggleKitchenTickets(b) => settings.with_kitchen_tickets(b),
+        Event::UpdateKitchenPrinter(s) => settings.with_kitchen_printer(Some(s)),
+        Event::ToggleCustomerDisplay(b) => settings.with_customer_display(b),
+        Event::UpdateSwishNumber(s) => settings.with_swish_number(Some(s)),
+        Event::UpdatePaymentTerminal(s) => settings.with_payment_terminal(Some(s)),
+        Event::UpdateServer(s) => settings.with_server(Some(s)),
+        Event::UpdateServerKey(s) => settings.with_server_key(Some(s)),
+        Event::UpdateRegister(s) => settings.with_register(Some(s)),
+        Event::UpdateSyncServer(s) => settings.with_sync_server(Some(s)),
+        _ => settings,
+    }
+}
+
+fn text(value: &Option<String>) -> String {
+    value.clone().unwrap_or_default()
+}
+
+fn receipt_column<'a>(settings: &backend::settings::Settings) -> Element<'a, Event> {
+    padded_column![
+        BIG_TEXT::new("Kvitton och rapporter"),
+        Text::new("Rubrik"),
+        SMALL_TEXT::new("Skrivs ut centrerat överst på kvitton och i rapporter."),
+        Column::with_children(settings.header().iter().enumerate().map(|(i, line)| {
+            row![
+                TextInput::new("", line)
+                    .on_input(move |s| Event::UpdateHeader(i, s))
+                    .padding(DEF_PADDING),
+                SquareButton::icon(Icon::Cross).on_press(Event::RemoveHeaderLine(i)),
+            ]
+            .align_items(Alignment::Center)
+            .into()
+        }))
+        .spacing(DEF_PADDING),
+        Button::new(Text::new("Lägg till rad"))
+            .on_press(Event::AddHeaderLine)
+            .padding(DEF_PADDING)
+            .style(theme::Container::Border),
+        Text::new("Organisationsnummer"),
+        TextInput::new("802000-0000", &text(settings.org_number()))
+            .on_input(Event::UpdateOrgNumber)
+            .padding(DEF_PADDING),
+        Text::new("Logga"),
+        SMALL_TEXT::new("Sökväg till en bild, lämna tomt för standardloggan."),
+        TextInput::new("Standard", &text(settings.logo()))
+            .on_input(Event::UpdateLogo)
+            .padding(DEF_PADDING),
+        Text::new("Kvittobredd (mm)"),
+        NumberInput::new(backend::settings::PAPER_WIDTHS, Event::UpdatePaperWidth, *settings.paper_width()),
+        BIG_TEXT::new("Flera kassor"),
+        Text::new("Kassaserver"),
+        SMALL_TEXT::new(
+            "Menyn, kvitton och rapporter delas med de andra kassorna genom servern. \
+             Lämna tomt för att bara använda den här datorn. Kräver omstart."
+        ),
+        TextInput::new("192.168.0.10:7878", &text(settings.server()))
+            .on_input(Event::UpdateServer)
+            .padding(DEF_PADDING),
+        Text::new("Kassans namn"),
+        SMALL_TEXT::new(
+            "Sparas på kvittona så att försäljningen kan skiljas åt, krävs för synkning. \
+             Kräver omstart."
+        ),
+        TextInput::new("Bar 1", &text(settings.register()))
+            .on_input(Event::UpdateRegister)
+            .padding(DEF_PADDING),
+        Text::new("Synkserver"),
+        SMALL_TEXT::new(
+            "Istället för kassaserver, kassan sparar allt här och skickar kvitton och menyändringar \
+             till servern när den går att nå. Kräver omstart."
+        ),
+        TextInput::new("192.168.0.10:7878", &text(settings.sync_server()))
+            .on_input(Event::UpdateSyncServer)
+            .padding(DEF_PADDING),
+        Text::new("Servernyckel"),
+        SMALL_TEXT::new(
+            "Nyckeln servern startades med, både kassaservern och synkservern kräver den. \
+             Kräver omstart."
+        ),
+        TextInput::new("", &text(settings.server_key()))
+            .on_input(Event::UpdateServerKey)
+            .secure(true)
+            .padding(DEF_PADDING),
+    ]
+    .into()
+}
+
+fn printing_column<'a>(settings: &backend::settings::Settings) -> Element<'a, Event> {
+    padded_column![
+        BIG_TEXT::new("Utskrift och export"),
+        Text::new("Exportmapp"),
+        SMALL_TEXT::new("Rapporter och menyexporter, lämna tomt för Dokument."),
+        TextInput::new("Dokument", &text(settings.export_dir()))
+            .on_input(Event::UpdateExportDir)
+            .padding(DEF_PADDING),
+        Text::new("Skrivare"),
+        SMALL_TEXT::new("Namnet på skrivaren för PDF:er, lämna tomt för standardskrivaren."),
+        TextInput::new("Kvittoskrivare", &text(settings.printer_name()))
+            .on_input(Event::UpdatePrinterName)
+            .padding(DEF_PADDING),
+        Text::new("Skrivarkommando"),
+        SMALL_TEXT::new(
+            "{file} byts mot sökvägen till PDF:en och {printer} mot skrivaren, \
+             sökvägen läggs sist om {file} saknas. Sätt citattecken runt delar med mellanslag. \
+             Lämna tomt för systemets skrivare."
+        ),
+        TextInput::new("lp -d {printer} {file}", &text(settings.printer_command()))
+            .on_input(Event::UpdatePrinterCommand)
+            .padding(DEF_PADDING),
+        Text::new("Kvittoskrivare (ESC/POS)"),
+        SMALL_TEXT::new(
+            "Kvitton skickas direkt till skrivaren, PDF skrivs ut om det misslyckas. \
+             Lämna tomt för att alltid skriva ut PDF."
+        ),
+        TextInput::new(
+            "tcp://192.168.0.50:9100, /dev/usb/lp0 eller COM3",
+            &text(settings.receipt_printer())
+        )
+        .on_input(Event::UpdateReceiptPrinter)
+        .padding(DEF_PADDING),
+        Checkbox::new("Öppna kassalådan vid kontantköp", *settings.cash_drawer())
+            .on_toggle(Event::ToggleCashDrawer),
+        Checkbox::new("Skriv ut köksbongar", *settings.kitchen_tickets())
+            .on_toggle(Event::ToggleKitchenTickets),
+        Text::new("Köksskrivare (ESC/POS)"),
+        SMALL_TEXT::new("Köksbongar med maten från varje köp, lämna tomt för kvittoskrivaren."),
+        TextInput::new("tcp://192.168.0.51:9100", &text(settings.kitchen_printer()))
+            .on_input(Event::UpdateKitchenPrinter)
+            .padding(DEF_PADDING),
+        BIG_TEXT::new("Betalning"),
+        Checkbox::new("Kundskärm", *settings.customer_display())
+            .on_toggle(Event::ToggleCustomerDisplay),
+        SMALL_TEXT::new("Ett andra fönster som visar kvittot, totalen och Swish-koden för kunden."),
+        Text::new("Swishnummer"),
+        SMALL_TEXT::new(
+            "Visas som QR-kod med beloppet vid Swishbetalning, lämna tomt för att stänga av."
+        ),
+        TextInput::new("123 456 78 90", &text(settings.swish_number()))
+            .on_input(Event::UpdateSwishNumber)
+            .padding(DEF_PADDING),
+        Text::new("Betalterminal"),
+        SMALL_TEXT::new(
+            "Kortbetalningar skickas till terminalen och sparas först när kunden har betalat. \
+             \"mock\" för att testa utan terminal, lämna tomt för att stänga av."
+        ),
+        TextInput::new("127.0.0.1:8080", &text(settings.payment_terminal()))
+            .on_input(Event::UpdatePaymentTerminal)
+            .padding(DEF_PADDING),
+    ]
+    .into()
+}
+
+impl From<Settings> for Element<'_, Message> {
+    fn from(settings: Settings) -> Self {
+        iced::widget::component(settings)
+    }
+}
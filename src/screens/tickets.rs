@@ -0,0 +1,133 @@
+use {
+    super::{Message, Sideffect, Tab},
+    crate::{
+        theme::{self, DEF_PADDING, RECEIPT_WIDTH},
+        widgets::{column, padded_column, row, BIG_TEXT, SMALL_TEXT},
+    },
+    backend::tickets::{Ticket, Validation},
+    iced::{
+        widget::{Column, Component, Container, Rule, Scrollable, Text, TextInput},
+        Alignment, Color, Element, Length,
+    },
+};
+
+pub struct Tickets {
+    tickets: Vec<Ticket>,
+    last: Option<Validation>,
+}
+
+#[derive(Default)]
+pub struct State {
+    input: String,
+}
+
+#[derive(Debug, Clone)]
+pub enum Event {
+    UpdateInput(String),
+    Scan,
+}
+
+impl Tickets {
+    pub fn new(tickets: Vec<Ticket>, last: Option<Validation>) -> Self {
+        Self { tickets, last }
+    }
+}
+
+impl Component<Message> for Tickets {
+    type State = State;
+    type Event = Event;
+
+    fn update(&mut self, state: &mut Self::State, event: Self::Event) -> Option<Message> {
+        match event {
+            Event::UpdateInput(s) => state.input = s,
+            //Scanners type the number followed by enter
+            Event::Scan if !state.input.trim().is_empty() => {
+                let number = std::mem::take(&mut state.input);
+                return Some(
+                    Sideffect::new(|| async move {
+                        let validation = Ticket::scan(number).await?;
+                        Ok(Tab::Tickets(Ticket::get_recents().await?, Some(validation)).into())
+                    })
+                    .into(),
+                );
+            }
+            Event::Scan => (),
+        }
+        None
+    }
+
+    fn view(&self, state: &Self::State) -> Element<Self::Event> {
+        let (title, info, color) = match &self.last {
+            Some(Validation::Valid(ticket)) => (
+                "Giltig biljett",
+                format!("{} ({})", ticket.item(), ticket.number()),
+                Color::from_rgb8(0xC0, 0xDA, 0x74),
+            ),
+            Some(Validation::AlreadyUsed(ticket)) => (
+                "Redan använd",
+                format!(
+                    "{} ({}) scannades {}",
+                    ticket.item(),
+                    ticket.number(),
+                    ticket
+                        .used()
+                        .map(|t| t.format("%F %T").to_string())
+                        .unwrap_or_default()
+                ),
+                Color::from_rgb8(0xFF, 0x6F, 0x59),
+            ),
+            Some(Validation::Unknown(number)) => (
+                "Okänd biljett",
+                format!("Ingen biljett har nummer {number}"),
+                Color::from_rgb8(0xFF, 0x6F, 0x59),
+            ),
+            None => ("Scanna biljett", String::new(), Color::WHITE),
+        };
+
+        row![
+            padded_column![
+                Container::new(
+                    column![BIG_TEXT::new(title), Text::new(info)]
+                        .align_items(Alignment::Center)
+                        .spacing(DEF_PADDING),
+                )
+                .center_x()
+                .center_y()
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .style(theme::Container::BorderFill(color)),
+                TextInput::new("Biljettnummer", &state.input)
+                    .on_input(Event::UpdateInput)
+                    .on_submit(Event::Scan)
+                    .padding(DEF_PADDING),
+            ],
+            Rule::vertical(DEF_PADDING),
+            padded_column![
+                BIG_TEXT::new("Senaste dygnet"),
+                Scrollable::new(
+                    Column::with_children(self.tickets.iter().map(|ticket| {
+                        SMALL_TEXT::new(match ticket.used() {
+                            Some(used) => format!(
+                                "{} {}, använd {}",
+                                ticket.number(),
+                                ticket.item(),
+                                used.format("%R")
+                            ),
+                            None => format!("{} {}", ticket.number(), ticket.item()),
+                        })
+                        .into()
+                    }))
+                    .spacing(DEF_PADDING),
+                ),
+            ]
+            .width(Length::Fixed(2.0 * RECEIPT_WIDTH)),
+        ]
+        .into()
+    }
+}
+
+impl From<Tickets> for Element<'_, Message> {
+    fn from(tickets: Tickets) -> Self {
+        iced::widget::component(tickets)
+    }
+}
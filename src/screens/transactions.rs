@@ -1,21 +1,29 @@
 use {
-    super::{Message, Sideffect},
+    super::{Message, Sideffect, TabId},
     crate::{
         icons::Icon,
         theme::{self, DEF_PADDING, RECEIPT_WIDTH},
-        widgets::{padded_column, row, SquareButton},
+        widgets::{column, padded_column, row, SquareButton, SMALL_TEXT},
+    },
+    backend::{
+        print_queue::{self, Job, JobState},
+        receipts::Receipt,
+        sync,
     },
-    backend::receipts::Receipt,
     chrono::{DateTime, Local},
     iced::{
-        widget::{scrollable::Direction, Component, Container, Row, Rule, Scrollable, Space},
-        Element, Length,
+        widget::{
+            scrollable::Direction, Column, Component, Container, Row, Rule, Scrollable, Space, Text,
+        },
+        Alignment, Element, Length,
     },
     indexmap::IndexMap,
 };
 
 pub struct Transactions {
     receipts: IndexMap<DateTime<Local>, Receipt>,
+    jobs: Vec<Job>,
+    sync: Option<sync::Status>,
 }
 
 #[derive(Default)]
@@ -31,11 +39,24 @@ pub enum Event {
     Select(DateTime<Local>),
     Deselect,
     Print,
+    Refresh,
+    RetryJob(u64),
+    RemoveJob(u64),
+    RetryChange(i64),
+    DiscardChange(i64),
 }
 
 impl Transactions {
-    pub fn new(receipts: IndexMap<DateTime<Local>, Receipt>) -> Self {
-        Self { receipts }
+    pub fn new(
+        receipts: IndexMap<DateTime<Local>, Receipt>,
+        jobs: Vec<Job>,
+        sync: Option<sync::Status>,
+    ) -> Self {
+        Self {
+            receipts,
+            jobs,
+            sync,
+        }
     }
 }
 
@@ -59,13 +80,47 @@ impl Component<Message> for Transactions {
                 if let Some(receipt) = state.selected.take() {
                     return Some(
                         Sideffect::new(|| async move {
-                            receipt.print().await?;
-                            Ok(().into())
+                            receipt.as_copy().print().await?;
+                            TabId::Transactions.load().await
                         })
                         .into(),
                     );
                 }
             }
+            Event::Refresh => {
+                return Some(Sideffect::new(|| TabId::Transactions.load()).into());
+            }
+            Event::RetryJob(id) => {
+                return Some(
+                    Sideffect::new(|| async move {
+                        print_queue::retry(id).await?;
+                        TabId::Transactions.load().await
+                    })
+                    .into(),
+                );
+            }
+            Event::RemoveJob(id) => {
+                print_queue::remove(id);
+                return Some(Sideffect::new(|| TabId::Transactions.load()).into());
+            }
+            Event::RetryChange(id) => {
+                return Some(
+                    Sideffect::new(|| async move {
+                        sync::retry(id).await?;
+                        TabId::Transactions.load().await
+                    })
+                    .into(),
+                );
+            }
+            Event::DiscardChange(id) => {
+                return Some(
+                    Sideffect::new(|| async move {
+                        sync::discard(id).await?;
+                        TabId::Transactions.load().await
+                    })
+                    .into(),
+                );
+            }
             _ => (),
         }
         None
@@ -93,8 +148,16 @@ impl Component<Message> for Transactions {
                     Some(ref rec) => Element::from(crate::receipt::Receipt::from(rec.clone())),
                     None => Space::new(Length::Fixed(RECEIPT_WIDTH), Length::Fill).into(),
                 },
+                //Only set when the register is named in the settings
+                match state.selected.as_ref().and_then(|rec| rec.register()) {
+                    Some(register) => Element::from(SMALL_TEXT::new(format!("Kassa: {register}"))),
+                    None => Space::with_height(Length::Shrink).into(),
+                },
+                jobs(&self.jobs),
+                sync_status(self.sync.as_ref()),
                 row![
                     SquareButton::icon(Icon::Cross).on_press(Event::Deselect),
+                    SquareButton::icon(Icon::Refresh).on_press(Event::Refresh),
                     Space::with_width(Length::Fill),
                     SquareButton::icon(Icon::Print).on_press(Event::Print),
                 ]
@@ -106,6 +169,98 @@ impl Component<Message> for Transactions {
     }
 }
 
+//Print jobs that have not gone through yet, the PDFs are kept so failed ones can be printed by hand
+fn jobs<'a>(jobs: &[Job]) -> Element<'a, Event> {
+    if jobs.is_empty() {
+        return Space::with_height(Length::Shrink).into();
+    }
+    column![
+        Text::new("Utskriftskö"),
+        Column::with_children(jobs.iter().map(|job| {
+            let state = match job.state() {
+                JobState::Pending => String::from("Väntar"),
+                JobState::Printing => format!("Skriver ut, försök {}", job.attempts()),
+                JobState::Printed => String::from("Utskriven som PDF"),
+                JobState::Failed(message) => format!("Misslyckades: {message}"),
+            };
+            let fallback = job
+                .fallback()
+                .as_ref()
+                .map(|reason| format!("ESC/POS-skrivaren kunde inte användas: {reason}"));
+            row![
+                column![
+                    SMALL_TEXT::new(format!(
+                        "{} {}",
+                        job.created().format("%T"),
+                        job.file()
+                            .file_name()
+                            .map(|name| name.to_string_lossy())
+                            .unwrap_or_default()
+                    )),
+                    SMALL_TEXT::new(state),
+                ]
+                .push_maybe(fallback.map(SMALL_TEXT::new))
+                .width(Length::Fill)
+                .height(Length::Shrink),
+                SquareButton::icon(Icon::Print).on_press_maybe(
+                    matches!(job.state(), JobState::Failed(_))
+                        .then_some(Event::RetryJob(*job.id()))
+                ),
+                SquareButton::icon(Icon::Trash).on_press(Event::RemoveJob(*job.id())),
+            ]
+            .spacing(DEF_PADDING)
+            .align_items(Alignment::Center)
+            .into()
+        }))
+        .spacing(DEF_PADDING),
+    ]
+    .spacing(DEF_PADDING)
+    .height(Length::Shrink)
+    .into()
+}
+
+//Changes refused by the sync server stay here until they are sent again or discarded
+fn sync_status<'a>(status: Option<&sync::Status>) -> Element<'a, Event> {
+    let Some(status) = status else {
+        return Space::with_height(Length::Shrink).into();
+    };
+    let synced = match status.synced() {
+        Some(time) => format!("Senast synkad {}", time.format("%T")),
+        None => String::from("Inte synkad sedan start"),
+    };
+    column![
+        Text::new("Synk"),
+        SMALL_TEXT::new(format!("{synced}, {} ändringar väntar", status.pending())),
+    ]
+    .push_maybe(
+        status
+            .error()
+            .as_ref()
+            .map(|e| SMALL_TEXT::new(format!("Senaste försöket misslyckades: {e}"))),
+    )
+    .push(
+        Column::with_children(status.rejected().iter().map(|rejected| {
+            row![
+                column![
+                    SMALL_TEXT::new(rejected.change().clone()),
+                    SMALL_TEXT::new(format!("Nekad av servern: {}", rejected.reason())),
+                ]
+                .width(Length::Fill)
+                .height(Length::Shrink),
+                SquareButton::icon(Icon::Refresh).on_press(Event::RetryChange(*rejected.id())),
+                SquareButton::icon(Icon::Trash).on_press(Event::DiscardChange(*rejected.id())),
+            ]
+            .spacing(DEF_PADDING)
+            .align_items(Alignment::Center)
+            .into()
+        }))
+        .spacing(DEF_PADDING),
+    )
+    .spacing(DEF_PADDING)
+    .height(Length::Shrink)
+    .into()
+}
+
 impl From<Transactions> for Element<'_, Message> {
     fn from(transactions: Transactions) -> Self {
         iced::widget::component(transactions)
@@ -11,6 +11,7 @@ use {
 
 pub struct Calc<'a, M> {
     multi: u32,
+    typed: String,
     on_set: Box<dyn Fn(u32) -> M + 'a>,
 }
 
@@ -28,9 +29,16 @@ impl<'a, M> Calc<'a, M> {
     {
         Self {
             multi: if multi > 0 { multi } else { 1 },
+            typed: String::new(),
             on_set: Box::new(on_set),
         }
     }
+
+    //Show input typed on a keyboard while the keypad is untouched
+    pub fn typed(mut self, typed: impl Into<String>) -> Self {
+        self.typed = typed.into();
+        self
+    }
 }
 
 impl<M> Component<M> for Calc<'_, M> {
@@ -72,7 +80,7 @@ impl<M> Component<M> for Calc<'_, M> {
                 Text::new(if *state != 0 {
                     format!("{state}")
                 } else {
-                    String::new()
+                    self.typed.clone()
                 })
                 .width(Length::Fill)
                 .horizontal_alignment(Horizontal::Right),
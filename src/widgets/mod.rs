@@ -1,8 +1,10 @@
 pub mod calc;
+pub mod key_listener;
 pub mod numberinput;
+pub mod qr;
 pub mod square_button;
 
-pub use {numberinput::NumberInput, square_button::SquareButton};
+pub use {key_listener::KeyListener, numberinput::NumberInput, square_button::SquareButton};
 
 #[allow(non_camel_case_types)]
 pub type BIG_TEXT = frost::text::Text<43>;
@@ -0,0 +1,34 @@
+use {
+    iced::widget::image::{Handle, Image},
+    qrcode::{Color, QrCode},
+};
+
+//Pixels per module and modules of white border
+const MODULE: usize = 8;
+const QUIET_ZONE: usize = 4;
+
+//Rendered to pixels since the QR widget in iced has to borrow its data
+pub fn qr_code(data: &str) -> Option<Image<Handle>> {
+    let code = QrCode::new(data).ok()?;
+    let width = code.width();
+    let colors = code.to_colors();
+    let size = (width + 2 * QUIET_ZONE) * MODULE;
+    let pixels = (0..size * size)
+        .flat_map(|i| {
+            let x = (i % size) / MODULE;
+            let y = (i / size) / MODULE;
+            let inside = (QUIET_ZONE..width + QUIET_ZONE).contains(&x)
+                && (QUIET_ZONE..width + QUIET_ZONE).contains(&y);
+            if inside && colors[(y - QUIET_ZONE) * width + x - QUIET_ZONE] == Color::Dark {
+                [0, 0, 0, 255]
+            } else {
+                [255; 4]
+            }
+        })
+        .collect::<Vec<u8>>();
+    Some(Image::new(Handle::from_pixels(
+        size as u32,
+        size as u32,
+        pixels,
+    )))
+}